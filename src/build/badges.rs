@@ -0,0 +1,98 @@
+//! This module is in charge of generating the SVG badges of contributors and
+//! repositories.
+
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike};
+use tracing::{debug, instrument};
+
+use crate::build::{
+    db, escape_xml, get_contributors_cards_info, output::Output, prettify_number, settings::BadgeTheme,
+};
+
+/// Path where the badges will be written to in the output directory.
+pub(crate) const BADGES_PATH: &str = "badges";
+
+/// Approximate width of a character in the badges text (in pixels).
+const CHAR_WIDTH: usize = 7;
+
+/// Horizontal padding of each of the badges sections (in pixels).
+const PADDING: usize = 10;
+
+/// Generate the badges of all contributors and repositories.
+#[instrument(skip_all, err)]
+pub(crate) fn generate_badges(
+    output: &mut Output,
+    contribs_db: &duckdb::Connection,
+    theme: &BadgeTheme,
+) -> Result<()> {
+    debug!("generating badges");
+
+    // Contributors badges
+    let badges_path = Path::new(BADGES_PATH);
+    for info in get_contributors_cards_info(contribs_db)? {
+        let year = DateTime::from_timestamp(info.first_contribution_ts, 0).map_or(0, |ts| ts.year());
+        let contributions = if info.total == 1 {
+            "contribution"
+        } else {
+            "contributions"
+        };
+        let message = format!("since {year} · {} {contributions}", prettify_number(info.total));
+        let badge = render_badge(&theme.label, &message, theme);
+        output.write(badges_path.join(format!("{}.svg", info.login)), badge)?;
+    }
+
+    // Repositories badges
+    let mut stmt = contribs_db.prepare(db::GET_REPOSITORIES_CONTRIBUTORS)?;
+    let rows = stmt.query_map([], |row| {
+        let owner: String = row.get(0)?;
+        let repository: String = row.get(1)?;
+        let contributors: i64 = row.get(2)?;
+        Ok((owner, repository, contributors))
+    })?;
+    for row in rows {
+        let (owner, repository, contributors) = row?;
+        let label = if contributors == 1 {
+            "contributor"
+        } else {
+            "contributors"
+        };
+        let badge = render_badge(label, &prettify_number(contributors), theme);
+        output.write(
+            badges_path.join("repo").join(owner).join(format!("{repository}.svg")),
+            badge,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Render a badge with the label and message provided.
+fn render_badge(label: &str, message: &str, theme: &BadgeTheme) -> String {
+    let label_width = label.chars().count() * CHAR_WIDTH + 2 * PADDING;
+    let message_width = message.chars().count() * CHAR_WIDTH + 2 * PADDING;
+    let width = label_width + message_width;
+    let label_x = label_width / 2;
+    let message_x = label_width + message_width / 2;
+    let label = escape_xml(label);
+    let message = escape_xml(message);
+    let label_color = escape_xml(&theme.label_color);
+    let color = escape_xml(&theme.color);
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}">
+    <title>{label}: {message}</title>
+    <clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff" /></clipPath>
+    <g clip-path="url(#r)">
+        <rect width="{label_width}" height="20" fill="{label_color}" />
+        <rect x="{label_width}" width="{message_width}" height="20" fill="{color}" />
+    </g>
+    <g fill="#fff" text-anchor="middle" font-family="Verdana,DejaVu Sans,Geneva,sans-serif" font-size="11">
+        <text x="{label_x}" y="14">{label}</text>
+        <text x="{message_x}" y="14">{message}</text>
+    </g>
+</svg>
+"##
+    )
+}
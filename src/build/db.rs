@@ -1,6 +1,76 @@
 //! This modules defines some SQL statements to setup and interact with the
 //! database.
 
+/// Compute the rankings of all contributors (by total contributions, per
+/// kind, per repository and per project).
+pub(crate) const COMPUTE_RANKINGS: &str = "
+BEGIN;
+
+INSERT INTO ranking
+SELECT
+    author_id,
+    'total',
+    NULL,
+    contributions,
+    rank() OVER (ORDER BY contributions DESC),
+    round(cume_dist() OVER (ORDER BY contributions DESC) * 100, 2),
+    count(*) OVER ()
+FROM (
+    SELECT author_id, count(*) AS contributions
+    FROM contribution
+    GROUP BY author_id
+);
+
+INSERT INTO ranking
+SELECT
+    author_id,
+    'kind',
+    kind,
+    contributions,
+    rank() OVER (PARTITION BY kind ORDER BY contributions DESC),
+    round(cume_dist() OVER (PARTITION BY kind ORDER BY contributions DESC) * 100, 2),
+    count(*) OVER (PARTITION BY kind)
+FROM (
+    SELECT author_id, kind, count(*) AS contributions
+    FROM contribution
+    GROUP BY author_id, kind
+);
+
+INSERT INTO ranking
+SELECT
+    author_id,
+    'repository',
+    format('{}/{}', owner, repository),
+    contributions,
+    rank() OVER (PARTITION BY owner, repository ORDER BY contributions DESC),
+    round(cume_dist() OVER (PARTITION BY owner, repository ORDER BY contributions DESC) * 100, 2),
+    count(*) OVER (PARTITION BY owner, repository)
+FROM (
+    SELECT author_id, owner, repository, count(*) AS contributions
+    FROM contribution
+    GROUP BY author_id, owner, repository
+);
+
+INSERT INTO ranking
+SELECT
+    author_id,
+    'project',
+    project,
+    contributions,
+    rank() OVER (PARTITION BY project ORDER BY contributions DESC),
+    round(cume_dist() OVER (PARTITION BY project ORDER BY contributions DESC) * 100, 2),
+    count(*) OVER (PARTITION BY project)
+FROM (
+    SELECT author_id, project, count(*) AS contributions
+    FROM contribution c
+    JOIN project_repository p
+    ON c.owner = p.owner AND c.repository = p.repository
+    GROUP BY author_id, project
+);
+
+COMMIT;
+";
+
 /// Copy commits from the temporary database to the cache database.
 pub(crate) const COPY_COMMITS_TO_CACHE: &str = "
 INSERT INTO cache.commit
@@ -8,17 +78,33 @@ SELECT * FROM commit
 ON CONFLICT DO NOTHING
 ";
 
-/// Copy issues from the temporary database to the cache database.
+/// Copy issues from the temporary database to the cache database (the state
+/// of issues already cached is updated, as it may have changed since then).
 pub(crate) const COPY_ISSUES_TO_CACHE: &str = "
 INSERT INTO cache.issue
 SELECT * FROM issue
-ON CONFLICT DO NOTHING
+ON CONFLICT DO UPDATE SET
+    state = excluded.state,
+    state_reason = excluded.state_reason,
+    closed_at = excluded.closed_at
 ";
 
-/// Copy pull requests from the temporary database to the cache database.
+/// Copy pull requests from the temporary database to the cache database (the
+/// state of pull requests already cached is updated, as it may have changed
+/// since then).
 pub(crate) const COPY_PULL_REQUESTS_TO_CACHE: &str = "
 INSERT INTO cache.pull_request
 SELECT * FROM pull_request
+ON CONFLICT DO UPDATE SET
+    state = excluded.state,
+    merged_at = excluded.merged_at,
+    closed_at = excluded.closed_at
+";
+
+/// Copy reviews from the temporary database to the cache database.
+pub(crate) const COPY_REVIEWS_TO_CACHE: &str = "
+INSERT INTO cache.review
+SELECT * FROM review
 ON CONFLICT DO NOTHING
 ";
 
@@ -48,7 +134,11 @@ CREATE TABLE IF NOT EXISTS contribution (
     author_id BIGINT,
     author_login VARCHAR,
     ts TIMESTAMP,
-    title VARCHAR
+    title VARCHAR,
+    state VARCHAR,
+    state_reason VARCHAR,
+    merged_at TIMESTAMP,
+    closed_at TIMESTAMP
 );
 ";
 
@@ -62,10 +152,47 @@ CREATE TABLE IF NOT EXISTS issue (
     author_login VARCHAR,
     ts TIMESTAMP,
     title VARCHAR,
+    state VARCHAR,
+    state_reason VARCHAR,
+    closed_at TIMESTAMP,
     PRIMARY KEY (owner, repository, number)
 );
 ";
 
+/// Create leaderboard contributor table from the leaderboard contributions.
+pub(crate) const CREATE_LEADERBOARD_CONTRIBUTOR_TABLE: &str = "
+CREATE TABLE leaderboard_contributor AS
+SELECT author_id AS id, first(author_login ORDER BY ts DESC) AS login
+FROM leaderboard_contribution
+GROUP BY author_id;
+";
+
+/// Create milestone table.
+pub(crate) const CREATE_MILESTONE_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS milestone (
+    author_id BIGINT,
+    id VARCHAR,
+    title VARCHAR,
+    ts BIGINT,
+    kind VARCHAR,
+    owner VARCHAR,
+    repository VARCHAR,
+    sha VARCHAR,
+    number BIGINT,
+    contribution_title VARCHAR,
+    contribution_ts BIGINT
+);
+";
+
+/// Create project repository table.
+pub(crate) const CREATE_PROJECT_REPOSITORY_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS project_repository (
+    project VARCHAR,
+    owner VARCHAR,
+    repository VARCHAR
+);
+";
+
 /// Create pull request table.
 pub(crate) const CREATE_PULL_REQUEST_TABLE: &str = "
 CREATE TABLE IF NOT EXISTS pull_request (
@@ -76,10 +203,103 @@ CREATE TABLE IF NOT EXISTS pull_request (
     author_login VARCHAR,
     ts TIMESTAMP,
     title VARCHAR,
+    state VARCHAR,
+    merged_at TIMESTAMP,
+    closed_at TIMESTAMP,
     PRIMARY KEY (owner, repository, number)
 );
 ";
 
+/// Create ranking table.
+pub(crate) const CREATE_RANKING_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS ranking (
+    author_id BIGINT,
+    scope VARCHAR,
+    name VARCHAR,
+    contributions BIGINT,
+    rank BIGINT,
+    top_percent DOUBLE,
+    contributors BIGINT
+);
+";
+
+/// Create review backfill table, which records the repositories whose pull
+/// requests reviews have already been backfilled.
+pub(crate) const CREATE_REVIEW_BACKFILL_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS review_backfill (
+    owner VARCHAR,
+    repository VARCHAR,
+    PRIMARY KEY (owner, repository)
+);
+";
+
+/// Create review table.
+pub(crate) const CREATE_REVIEW_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS review (
+    owner VARCHAR,
+    repository VARCHAR,
+    id BIGINT,
+    number BIGINT,
+    author_id BIGINT,
+    author_login VARCHAR,
+    ts TIMESTAMP,
+    PRIMARY KEY (owner, repository, id)
+);
+";
+
+/// Create state backfill table, which records the repositories whose issues
+/// and pull requests state information has already been backfilled.
+pub(crate) const CREATE_STATE_BACKFILL_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS state_backfill (
+    owner VARCHAR,
+    repository VARCHAR,
+    PRIMARY KEY (owner, repository)
+);
+";
+
+/// Delete rankings that don't meet the thresholds provided (maximum top
+/// percent and minimum number of contributions).
+pub(crate) const DELETE_RANKINGS_BELOW_THRESHOLDS: &str = "
+DELETE FROM ranking
+WHERE top_percent > ?
+OR contributions < ?;
+";
+
+/// Get all contributions sorted by author and timestamp. Reviews are
+/// included as well (they are only used to compute milestones), except the
+/// ones of the authors of the pull requests (i.e. replies to review comments).
+pub(crate) const GET_ALL_CONTRIBUTIONS_BY_AUTHOR: &str = "
+SELECT
+    author_id,
+    kind,
+    owner,
+    repository,
+    sha,
+    number,
+    title,
+    extract('epoch' FROM ts)::BIGINT AS ts,
+    extract('epoch' FROM merged_at)::BIGINT AS merged_at,
+    state_reason
+FROM contribution
+UNION ALL
+SELECT
+    r.author_id,
+    'review' AS kind,
+    r.owner,
+    r.repository,
+    NULL AS sha,
+    r.number,
+    pr.title,
+    extract('epoch' FROM r.ts)::BIGINT AS ts,
+    NULL AS merged_at,
+    NULL AS state_reason
+FROM cache.review r
+LEFT JOIN cache.pull_request pr USING (owner, repository, number)
+WHERE r.author_id IN (SELECT author_id FROM contribution)
+AND r.author_id IS DISTINCT FROM pr.author_id
+ORDER BY author_id ASC, ts ASC, owner ASC, repository ASC, title ASC, number ASC, sha ASC;
+";
+
 /// Get contributions summaries of all contributors.
 pub(crate) const GET_ALL_CONTRIBUTORS_SUMMARIES: &str = "
 SELECT
@@ -113,6 +333,20 @@ SELECT
                             FROM contribution
                             WHERE kind = 'pull_request'
                             AND author_id = contributor.author_id
+                        ),
+                        'pull_request_merged', (
+                            SELECT count(*)
+                            FROM contribution
+                            WHERE kind = 'pull_request'
+                            AND merged_at IS NOT NULL
+                            AND author_id = contributor.author_id
+                        ),
+                        'issue_completed', (
+                            SELECT count(*)
+                            FROM contribution
+                            WHERE kind = 'issue'
+                            AND state_reason = 'completed'
+                            AND author_id = contributor.author_id
                         )
                     )
                 )
@@ -135,6 +369,46 @@ SELECT
             FROM contribution
             WHERE author_id = contributor.author_id
         ),
+        'activity', (
+            SELECT json_object(
+                'months', (
+                    SELECT to_json(map(list(month ORDER BY month ASC), list(total ORDER BY month ASC)))
+                    FROM (
+                        SELECT strftime(ts, '%Y-%m') AS month, count(*) AS total
+                        FROM contribution
+                        WHERE author_id = contributor.author_id
+                        GROUP BY month
+                    )
+                ),
+                'weekly_streak', (
+                    SELECT json_object(
+                        'longest', coalesce(max(weeks), 0),
+                        'last', coalesce(arg_max(weeks, last_week), 0),
+                        'last_week', max(last_week)
+                    )
+                    FROM (
+                        SELECT count(*) AS weeks, max(week) AS last_week
+                        FROM (
+                            SELECT week, week - row_number() OVER (ORDER BY week) AS streak
+                            FROM (
+                                SELECT DISTINCT (date_trunc('week', ts)::DATE - DATE '1970-01-05') // 7 AS week
+                                FROM contribution
+                                WHERE author_id = contributor.author_id
+                            )
+                        )
+                        GROUP BY streak
+                    )
+                ),
+                'most_active_day_of_week', (
+                    SELECT dayname(ts)
+                    FROM contribution
+                    WHERE author_id = contributor.author_id
+                    GROUP BY isodow(ts), dayname(ts)
+                    ORDER BY count(*) DESC, isodow(ts) ASC
+                    LIMIT 1
+                )
+            )
+        ),
         'first_commit', (
             SELECT json_object(
                 'owner', owner,
@@ -161,6 +435,109 @@ SELECT
             WHERE author_id = contributor.author_id
             ORDER BY ts ASC, owner ASC, repository ASC, title ASC, number ASC, sha ASC
             LIMIT 1
+        ),
+        'milestones', (
+            SELECT list(
+                json_object(
+                    'id', id,
+                    'title', title,
+                    'ts', ts,
+                    'contribution', json_object(
+                        'kind', kind,
+                        'owner', owner,
+                        'repository', repository,
+                        'sha', sha,
+                        'number', number,
+                        'title', contribution_title,
+                        'ts', contribution_ts
+                    )
+                )
+                ORDER BY ts ASC, id ASC
+            )
+            FROM milestone
+            WHERE author_id = contributor.author_id
+        ),
+        'ranking', json_object(
+            'total', (
+                SELECT json_object(
+                    'rank', rank,
+                    'top_percent', top_percent,
+                    'contributors', contributors
+                )
+                FROM ranking
+                WHERE author_id = contributor.author_id
+                AND scope = 'total'
+            ),
+            'by_kind', (
+                SELECT json_group_object(
+                    name,
+                    json_object(
+                        'rank', rank,
+                        'top_percent', top_percent,
+                        'contributors', contributors
+                    )
+                )
+                FROM ranking
+                WHERE author_id = contributor.author_id
+                AND scope = 'kind'
+            ),
+            'repositories', (
+                SELECT list(
+                    json_object(
+                        'name', name,
+                        'rank', rank,
+                        'top_percent', top_percent,
+                        'contributors', contributors
+                    )
+                    ORDER BY top_percent ASC, name ASC
+                )
+                FROM ranking
+                WHERE author_id = contributor.author_id
+                AND scope = 'repository'
+            ),
+            'projects', (
+                SELECT list(
+                    json_object(
+                        'name', name,
+                        'rank', rank,
+                        'top_percent', top_percent,
+                        'contributors', contributors
+                    )
+                    ORDER BY top_percent ASC, name ASC
+                )
+                FROM ranking
+                WHERE author_id = contributor.author_id
+                AND scope = 'project'
+            )
+        ),
+        'last_contribution', (
+            SELECT json_object(
+                'kind', kind,
+                'owner', owner,
+                'repository', repository,
+                'sha', sha,
+                'number', number,
+                'title', title,
+                'ts', extract('epoch' FROM ts)::BIGINT
+            ) FROM contribution
+            WHERE author_id = contributor.author_id
+            ORDER BY ts DESC, owner ASC, repository ASC, title ASC, number ASC, sha ASC
+            LIMIT 1
+        ),
+        'first_merged_pull_request', (
+            SELECT json_object(
+                'kind', kind,
+                'owner', owner,
+                'repository', repository,
+                'number', number,
+                'title', title,
+                'ts', extract('epoch' FROM merged_at)::BIGINT
+            ) FROM contribution
+            WHERE author_id = contributor.author_id
+            AND kind = 'pull_request'
+            AND merged_at IS NOT NULL
+            ORDER BY merged_at ASC, owner ASC, repository ASC, number ASC
+            LIMIT 1
         )
     ) AS summary
 FROM (
@@ -170,14 +547,31 @@ FROM (
 ) AS contributor
 ";
 
-/// Get the id and login of all contributors.
-pub(crate) const GET_CONTRIBUTORS: &str = "
-SELECT json_group_object(login, id)
-FROM (
-    SELECT author_id AS id, first(author_login ORDER BY ts DESC) AS login
-    FROM contribution
-    GROUP BY author_id
-) AS contributor;
+/// Get the information displayed in the cards images of all contributors.
+pub(crate) const GET_CONTRIBUTORS_CARDS_INFO: &str = "
+SELECT
+    first(author_login ORDER BY ts DESC) AS login,
+    count(*) AS total,
+    count(DISTINCT format('{}/{}', owner, repository)) AS repositories,
+    extract('epoch' FROM min(ts))::BIGINT AS first_contribution_ts,
+    first(format('{}/{}', owner, repository) ORDER BY ts ASC, owner ASC, repository ASC) AS first_contribution_repository
+FROM contribution
+GROUP BY author_id;
+";
+
+/// Get the number of contributors.
+pub(crate) const GET_CONTRIBUTORS_COUNT: &str = "
+SELECT count(DISTINCT author_id) FROM contribution;
+";
+
+/// Get the login and the date of the last contribution of all contributors.
+pub(crate) const GET_CONTRIBUTORS_LAST_CONTRIBUTION_DATE: &str = "
+SELECT
+    first(author_login ORDER BY ts DESC) AS login,
+    strftime(max(ts), '%Y-%m-%d') AS last_contribution_date
+FROM contribution
+GROUP BY author_id
+ORDER BY login ASC;
 ";
 
 /// Get last commit timestamp.
@@ -190,6 +584,13 @@ ORDER BY ts DESC
 LIMIT 1;
 ";
 
+/// Get the timestamp of the most recent contribution (formatted as expected
+/// by Atom feeds).
+pub(crate) const GET_LAST_CONTRIBUTION_TS: &str = "
+SELECT strftime(coalesce(max(ts), 'epoch'::TIMESTAMP), '%Y-%m-%dT%H:%M:%SZ')
+FROM contribution;
+";
+
 /// Get last issue or pull request timestamp (we'll pick the older).
 pub(crate) const GET_LAST_ISSUE_OR_PULL_REQUEST_TS: &str = "
 (
@@ -213,6 +614,379 @@ ORDER BY ts ASC
 LIMIT 1;
 ";
 
+/// Get all leaderboards (all time, per year, per repository and per project)
+/// from the leaderboard contribution table, limiting the number of
+/// contributors in each of them to the size provided.
+pub(crate) const GET_LEADERBOARDS: &str = "
+WITH leaderboard AS (
+    SELECT 'all' AS scope, 'all' AS name, author_id
+    FROM leaderboard_contribution
+    UNION ALL
+    SELECT 'year', extract('year' FROM ts)::VARCHAR, author_id
+    FROM leaderboard_contribution
+    UNION ALL
+    SELECT 'repository', format('{}/{}', owner, repository), author_id
+    FROM leaderboard_contribution
+    UNION ALL
+    SELECT 'project', project, author_id
+    FROM leaderboard_contribution c
+    JOIN project_repository p
+    ON c.owner = p.owner AND c.repository = p.repository
+)
+SELECT
+    scope,
+    name,
+    json_object(
+        'scope', scope,
+        'name', name,
+        'contributors', list(
+            json_object(
+                'rank', rank,
+                'id', author_id,
+                'login', author_login,
+                'contributions', contributions
+            )
+            ORDER BY rank ASC, author_login ASC
+        )
+    )
+FROM (
+    SELECT
+        scope,
+        name,
+        author_id,
+        login AS author_login,
+        contributions,
+        rank() OVER (PARTITION BY scope, name ORDER BY contributions DESC) AS rank,
+        row_number() OVER (PARTITION BY scope, name ORDER BY contributions DESC, login ASC) AS position
+    FROM (
+        SELECT scope, name, author_id, count(*) AS contributions
+        FROM leaderboard
+        GROUP BY scope, name, author_id
+    ) l
+    JOIN leaderboard_contributor c
+    ON l.author_id = c.id
+)
+WHERE position <= ?
+GROUP BY scope, name
+ORDER BY scope ASC, name ASC;
+";
+
+/// Get the contributors whose first contribution happened within the number
+/// of days provided, overall (project is null) and per project.
+pub(crate) const GET_NEW_CONTRIBUTORS: &str = "
+WITH first_contribution AS (
+    SELECT
+        NULL::VARCHAR AS project,
+        author_id,
+        min(ts) AS ts,
+        arg_min(format('{}/{}', owner, repository), ts) AS repository
+    FROM contribution
+    GROUP BY author_id
+    UNION ALL
+    SELECT
+        project,
+        author_id,
+        min(ts) AS ts,
+        arg_min(format('{}/{}', c.owner, c.repository), ts) AS repository
+    FROM contribution c
+    JOIN project_repository p
+    ON c.owner = p.owner AND c.repository = p.repository
+    GROUP BY project, author_id
+),
+contributor AS (
+    SELECT author_id AS id, first(author_login ORDER BY ts DESC) AS login
+    FROM contribution
+    GROUP BY author_id
+)
+SELECT
+    f.project,
+    c.login,
+    strftime(f.ts, '%Y-%m-%dT%H:%M:%SZ'),
+    f.repository
+FROM first_contribution f
+JOIN contributor c
+ON f.author_id = c.id
+WHERE f.ts >= current_date - to_days($1::INTEGER)
+ORDER BY f.project ASC NULLS FIRST, f.ts DESC, c.login ASC;
+";
+
+/// Get the numbers of the pull requests of the repository provided.
+pub(crate) const GET_PULL_REQUESTS_NUMBERS: &str = "
+SELECT number
+FROM pull_request
+WHERE owner = ?
+AND repository = ?
+ORDER BY number ASC;
+";
+
+/// Get the number of contributors of each repository.
+pub(crate) const GET_REPOSITORIES_CONTRIBUTORS: &str = "
+SELECT owner, repository, count(DISTINCT author_id) AS contributors
+FROM contribution
+GROUP BY owner, repository;
+";
+
+/// Get the search index n-grams shards, grouping the contributors (login ->
+/// id) by each of the lowercased trigrams found in their login.
+pub(crate) const GET_SEARCH_INDEX_NGRAMS_SHARDS: &str = "
+WITH contributor AS (
+    SELECT author_id AS id, first(author_login ORDER BY ts DESC) AS login
+    FROM contribution
+    GROUP BY author_id
+),
+contributor_ngram AS (
+    SELECT DISTINCT lower(substr(login, i, 3)) AS ngram, login, id
+    FROM (
+        SELECT login, id, unnest(generate_series(1, length(login) - 2)) AS i
+        FROM contributor
+    )
+)
+SELECT ngram, json_group_object(login, id)
+FROM contributor_ngram
+GROUP BY ngram
+ORDER BY ngram;
+";
+
+/// Get the search index shards, grouping the contributors (login -> id) by
+/// the lowercased prefix of their login.
+pub(crate) const GET_SEARCH_INDEX_SHARDS: &str = "
+SELECT lower(left(login, $1::INTEGER)) AS prefix, json_group_object(login, id)
+FROM (
+    SELECT author_id AS id, first(author_login ORDER BY ts DESC) AS login
+    FROM contribution
+    GROUP BY author_id
+) AS contributor
+GROUP BY prefix
+ORDER BY prefix;
+";
+
+/// Get site-wide statistics computed from all the contributions. The number
+/// of contributors whose last contribution happened on each of the days of
+/// the year before the date provided is included so that the active
+/// contributors can be counted in the web application (relative to the
+/// current date, not the build one).
+pub(crate) const GET_STATS: &str = "
+SELECT json_object(
+    'contributors', (
+        SELECT count(DISTINCT author_id)
+        FROM contribution
+    ),
+    'contributions', json_object(
+        'total', (
+            SELECT count(*)
+            FROM contribution
+        ),
+        'by_kind', (
+            SELECT json_group_object(kind, total)
+            FROM (
+                SELECT kind, count(*) AS total
+                FROM contribution
+                GROUP BY kind
+            )
+        )
+    ),
+    'contributors_per_year', (
+        SELECT to_json(map(list(year::VARCHAR ORDER BY year ASC), list(total ORDER BY year ASC)))
+        FROM (
+            SELECT extract('year' FROM ts) AS year, count(DISTINCT author_id) AS total
+            FROM contribution
+            GROUP BY year
+        )
+    ),
+    'new_contributors_per_month', (
+        SELECT to_json(map(list(month ORDER BY month ASC), list(total ORDER BY month ASC)))
+        FROM (
+            SELECT strftime(first_ts, '%Y-%m') AS month, count(*) AS total
+            FROM (
+                SELECT author_id, min(ts) AS first_ts
+                FROM contribution
+                GROUP BY author_id
+            )
+            GROUP BY month
+        )
+    ),
+    'top_repositories', (
+        SELECT list(
+            json_object(
+                'name', name,
+                'contributions', contributions,
+                'contributors', contributors
+            )
+            ORDER BY contributions DESC, name ASC
+        )
+        FROM (
+            SELECT
+                format('{}/{}', owner, repository) AS name,
+                count(*) AS contributions,
+                count(DISTINCT author_id) AS contributors
+            FROM contribution
+            GROUP BY owner, repository
+            ORDER BY contributions DESC, name ASC
+            LIMIT 10
+        )
+    ),
+    'last_contributions_per_day', (
+        SELECT to_json(map(list(day ORDER BY day ASC), list(total ORDER BY day ASC)))
+        FROM (
+            SELECT strftime(last_ts, '%Y-%m-%d') AS day, count(*) AS total
+            FROM (
+                SELECT author_id, max(ts) AS last_ts
+                FROM contribution
+                GROUP BY author_id
+            )
+            WHERE last_ts >= $1::DATE - INTERVAL 365 DAY
+            GROUP BY day
+        )
+    )
+);
+";
+
+/// Get year in review (wrapped) summaries of all contributors who contributed
+/// in the year provided.
+pub(crate) const GET_WRAPPED_SUMMARIES: &str = "
+WITH year_contribution AS (
+    SELECT *
+    FROM contribution
+    WHERE extract('year' FROM ts) = $1
+)
+SELECT
+    author_login AS contributor,
+    (
+        SELECT count(*)
+        FROM year_contribution
+        WHERE author_id = contributor.author_id
+    ) AS total,
+    (
+        SELECT count(DISTINCT format('{}/{}', owner, repository))
+        FROM year_contribution
+        WHERE author_id = contributor.author_id
+    ) AS repositories,
+    json_object(
+        'year', $1,
+        'id', author_id,
+        'login', author_login,
+        'contributions', json_object(
+            'total', (
+                SELECT count(*)
+                FROM year_contribution
+                WHERE author_id = contributor.author_id
+            ),
+            'by_kind', (
+                SELECT json_group_object(kind, total)
+                FROM (
+                    SELECT kind, count(*) AS total
+                    FROM year_contribution
+                    WHERE author_id = contributor.author_id
+                    GROUP BY kind
+                )
+            )
+        ),
+        'top_repositories', (
+            SELECT list(
+                json_object('name', name, 'contributions', contributions)
+                ORDER BY contributions DESC, name ASC
+            )
+            FROM (
+                SELECT format('{}/{}', owner, repository) AS name, count(*) AS contributions
+                FROM year_contribution
+                WHERE author_id = contributor.author_id
+                GROUP BY owner, repository
+                ORDER BY contributions DESC, name ASC
+                LIMIT 5
+            )
+        ),
+        'busiest_month', (
+            SELECT json_object('month', month, 'contributions', contributions)
+            FROM (
+                SELECT strftime(ts, '%Y-%m') AS month, count(*) AS contributions
+                FROM year_contribution
+                WHERE author_id = contributor.author_id
+                GROUP BY month
+            )
+            ORDER BY contributions DESC, month ASC
+            LIMIT 1
+        ),
+        'new_repositories', (
+            SELECT list(format('{}/{}', owner, repository) ORDER BY owner ASC, repository ASC)
+            FROM (
+                SELECT owner, repository
+                FROM contribution
+                WHERE author_id = contributor.author_id
+                GROUP BY owner, repository
+                HAVING extract('year' FROM min(ts)) = $1
+            )
+        ),
+        'first_contribution', (
+            SELECT json_object(
+                'kind', kind,
+                'owner', owner,
+                'repository', repository,
+                'sha', sha,
+                'number', number,
+                'title', title,
+                'ts', extract('epoch' FROM ts)::BIGINT
+            ) FROM year_contribution
+            WHERE author_id = contributor.author_id
+            ORDER BY ts ASC, owner ASC, repository ASC, title ASC, number ASC, sha ASC
+            LIMIT 1
+        ),
+        'last_contribution', (
+            SELECT json_object(
+                'kind', kind,
+                'owner', owner,
+                'repository', repository,
+                'sha', sha,
+                'number', number,
+                'title', title,
+                'ts', extract('epoch' FROM ts)::BIGINT
+            ) FROM year_contribution
+            WHERE author_id = contributor.author_id
+            ORDER BY ts DESC, owner ASC, repository ASC, title ASC, number ASC, sha ASC
+            LIMIT 1
+        )
+    ) AS summary
+FROM (
+    SELECT author_id, first(author_login ORDER BY ts DESC) as author_login
+    FROM contribution
+    GROUP BY author_id
+    HAVING author_id IN (SELECT author_id FROM year_contribution)
+) AS contributor
+";
+
+/// Check if there are issues or pull requests without state information (i.e.
+/// collected before it was tracked) that haven't been backfilled yet.
+pub(crate) const HAS_ISSUES_OR_PULL_REQUESTS_WITHOUT_STATE: &str = "
+SELECT NOT EXISTS (
+    SELECT 1
+    FROM state_backfill
+    WHERE owner = $1
+    AND repository = $2
+) AND (EXISTS (
+    SELECT 1
+    FROM issue
+    WHERE owner = $1
+    AND repository = $2
+    AND state IS NULL
+) OR EXISTS (
+    SELECT 1
+    FROM pull_request
+    WHERE owner = $1
+    AND repository = $2
+    AND state IS NULL
+));
+";
+
+/// Check if the reviews of the pull requests of the repository provided have
+/// already been backfilled.
+pub(crate) const HAS_REVIEWS_BACKFILL: &str = "
+SELECT EXISTS (
+    SELECT 1
+    FROM review_backfill
+    WHERE owner = ?
+    AND repository = ?
+);
+";
+
 /// Load commits from json file.
 pub(crate) const LOAD_COMMITS_FROM_JSON_FILE: &str = "
 INSERT INTO commit
@@ -263,7 +1037,10 @@ INSERT INTO contribution (
     author_id,
     author_login,
     ts,
-    title
+    title,
+    state,
+    state_reason,
+    closed_at
 )
 SELECT
     'issue',
@@ -273,7 +1050,10 @@ SELECT
     author_id,
     author_login,
     ts,
-    title
+    title,
+    state,
+    state_reason,
+    closed_at
 FROM cache.issue;
 
 INSERT INTO contribution (
@@ -284,7 +1064,10 @@ INSERT INTO contribution (
     author_id,
     author_login,
     ts,
-    title
+    title,
+    state,
+    merged_at,
+    closed_at
 )
 SELECT
     'pull_request',
@@ -294,7 +1077,10 @@ SELECT
     author_id,
     author_login,
     ts,
-    title
+    title,
+    state,
+    merged_at,
+    closed_at
 FROM cache.pull_request;
 
 COMMIT;
@@ -310,8 +1096,20 @@ SELECT
     user.id as author_id,
     user.login as author_login,
     created_at as ts,
-    title
-FROM read_json(?)
+    title,
+    state,
+    state_reason,
+    closed_at
+FROM read_json(?, columns = {
+    number: 'BIGINT',
+    user: 'STRUCT(id BIGINT, login VARCHAR)',
+    created_at: 'TIMESTAMP',
+    title: 'VARCHAR',
+    html_url: 'VARCHAR',
+    state: 'VARCHAR',
+    state_reason: 'VARCHAR',
+    closed_at: 'TIMESTAMP'
+})
 WHERE regexp_matches(html_url, '.*/issues/\d+$')
 ON CONFLICT DO NOTHING;
 ";
@@ -326,8 +1124,118 @@ SELECT
     user.id as author_id,
     user.login as author_login,
     created_at as ts,
-    title
-FROM read_json(?)
+    title,
+    state,
+    pull_request.merged_at as merged_at,
+    closed_at
+FROM read_json(?, columns = {
+    number: 'BIGINT',
+    user: 'STRUCT(id BIGINT, login VARCHAR)',
+    created_at: 'TIMESTAMP',
+    title: 'VARCHAR',
+    html_url: 'VARCHAR',
+    state: 'VARCHAR',
+    closed_at: 'TIMESTAMP',
+    pull_request: 'STRUCT(merged_at TIMESTAMP)'
+})
 WHERE regexp_matches(html_url, '.*/pull/\d+$')
 ON CONFLICT DO NOTHING;
 ";
+
+/// Load the reviews of a pull request from json file (only the submitted
+/// ones).
+pub(crate) const LOAD_REVIEWS_FROM_JSON_FILE: &str = "
+INSERT INTO review
+SELECT
+    ? AS owner,
+    ? AS repository,
+    id,
+    ? AS number,
+    user.id as author_id,
+    user.login as author_login,
+    submitted_at as ts
+FROM read_json(?, columns = {
+    id: 'BIGINT',
+    user: 'STRUCT(id BIGINT, login VARCHAR)',
+    state: 'VARCHAR',
+    submitted_at: 'TIMESTAMP'
+})
+WHERE user.id IS NOT NULL
+AND submitted_at IS NOT NULL
+AND state <> 'PENDING'
+ON CONFLICT DO NOTHING;
+";
+
+/// Add the state columns to an issue table created by a previous version.
+pub(crate) const MIGRATE_ISSUE_TABLE: &str = "
+ALTER TABLE issue ADD COLUMN IF NOT EXISTS state VARCHAR;
+ALTER TABLE issue ADD COLUMN IF NOT EXISTS state_reason VARCHAR;
+ALTER TABLE issue ADD COLUMN IF NOT EXISTS closed_at TIMESTAMP;
+";
+
+/// Add the state columns to a pull request table created by a previous
+/// version.
+pub(crate) const MIGRATE_PULL_REQUEST_TABLE: &str = "
+ALTER TABLE pull_request ADD COLUMN IF NOT EXISTS state VARCHAR;
+ALTER TABLE pull_request ADD COLUMN IF NOT EXISTS merged_at TIMESTAMP;
+ALTER TABLE pull_request ADD COLUMN IF NOT EXISTS closed_at TIMESTAMP;
+";
+
+/// Prepare the leaderboard contribution table, containing the contributions
+/// of the kinds provided ($1, comma separated, all when empty) that don't
+/// belong to bots ($2, login pattern, none when empty) or to any of the
+/// excluded logins ($3, comma separated and lowercased, as logins are case
+/// insensitive).
+pub(crate) const PREPARE_LEADERBOARD_CONTRIBUTION_TABLE: &str = "
+CREATE TABLE leaderboard_contribution AS
+SELECT *
+FROM contribution
+WHERE (
+    $1 = ''
+    OR list_contains(string_split($1, ','), kind)
+    OR (
+        kind = 'pull_request'
+        AND merged_at IS NOT NULL
+        AND list_contains(string_split($1, ','), 'pull_request_merged')
+    )
+    OR (
+        kind = 'issue'
+        AND state_reason = 'completed'
+        AND list_contains(string_split($1, ','), 'issue_completed')
+    )
+)
+AND ($2 = '' OR NOT regexp_matches(author_login, $2))
+AND NOT list_contains(string_split($3, ','), lower(author_login));
+";
+
+/// Record that the reviews of the pull requests of the repository provided
+/// have been backfilled.
+pub(crate) const REGISTER_REVIEWS_BACKFILL: &str = "
+INSERT OR IGNORE INTO cache.review_backfill VALUES ($1, $2);
+";
+
+/// Record that the state information of the issues and pull requests of the
+/// repository provided has been backfilled.
+pub(crate) const REGISTER_STATE_BACKFILL: &str = "
+INSERT OR IGNORE INTO cache.state_backfill VALUES ($1, $2);
+";
+
+/// Resolve the repositories of each project, replacing the entries in the
+/// project repository table with the distinct repositories contributed to
+/// that match them (i.e. wildcards are expanded and duplicates removed, so
+/// that contributions are counted only once per project).
+pub(crate) const RESOLVE_PROJECT_REPOSITORIES: &str = "
+BEGIN;
+
+CREATE TEMPORARY TABLE resolved_project_repository AS
+SELECT DISTINCT p.project, r.owner, r.repository
+FROM (SELECT DISTINCT owner, repository FROM contribution) r
+JOIN project_repository p
+ON r.owner = p.owner AND (p.repository = '*' OR r.repository = p.repository);
+
+DELETE FROM project_repository;
+INSERT INTO project_repository SELECT * FROM resolved_project_repository;
+DROP TABLE resolved_project_repository;
+
+COMMIT;
+";
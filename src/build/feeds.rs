@@ -0,0 +1,125 @@
+//! This module is in charge of generating the Atom feeds of new contributors.
+
+use std::{collections::BTreeMap, fmt::Write, path::Path};
+
+use anyhow::Result;
+use tracing::{debug, instrument};
+
+use crate::build::{contributor_url, db, escape_xml, output::Output, settings::Settings};
+
+/// Path where the feeds will be written to in the output directory.
+pub(crate) const FEEDS_PATH: &str = "feeds";
+
+/// New contributor entry in a feed.
+#[derive(Debug, Clone)]
+struct NewContributor {
+    login: String,
+    first_contribution_ts: String,
+    first_contribution_repository: String,
+}
+
+/// Generate the Atom feeds listing the contributors whose first contribution
+/// happened within the period configured (a general one and, when projects
+/// are configured, one per project).
+#[instrument(skip_all, err)]
+pub(crate) fn generate_feeds(
+    output: &mut Output,
+    contribs_db: &duckdb::Connection,
+    settings: &Settings,
+) -> Result<()> {
+    if !settings.feeds.enabled {
+        return Ok(());
+    }
+
+    debug!("generating feeds");
+
+    // Get new contributors from database (grouped by project)
+    let mut new_contributors: BTreeMap<Option<String>, Vec<NewContributor>> = BTreeMap::new();
+    let mut stmt = contribs_db.prepare(db::GET_NEW_CONTRIBUTORS)?;
+    let rows = stmt.query_map([settings.feeds.days], |row| {
+        let project: Option<String> = row.get(0)?;
+        let new_contributor = NewContributor {
+            login: row.get(1)?,
+            first_contribution_ts: row.get(2)?,
+            first_contribution_repository: row.get(3)?,
+        };
+        Ok((project, new_contributor))
+    })?;
+    for row in rows {
+        let (project, new_contributor) = row?;
+        new_contributors.entry(project).or_default().push(new_contributor);
+    }
+
+    // Empty feeds use the timestamp of the last contribution as their last
+    // update time, so that they don't change between builds
+    let last_contribution_ts: String =
+        contribs_db.query_row(db::GET_LAST_CONTRIBUTION_TS, [], |row| row.get(0))?;
+
+    // Write general feed
+    let feeds_path = Path::new(FEEDS_PATH);
+    let feed = render_feed(
+        settings,
+        &last_contribution_ts,
+        &format!("{} new contributors", settings.theme.og_title),
+        "new-contributors.xml",
+        new_contributors.get(&None).map_or(&[], Vec::as_slice),
+    )?;
+    output.write(feeds_path.join("new-contributors.xml"), feed)?;
+
+    // Write projects feeds
+    for project in &settings.projects {
+        let file_name = format!("projects/{}.xml", project.name);
+        let feed = render_feed(
+            settings,
+            &last_contribution_ts,
+            &format!("{} new contributors", project.name),
+            &file_name,
+            new_contributors.get(&Some(project.name.clone())).map_or(&[], Vec::as_slice),
+        )?;
+        output.write(feeds_path.join(&file_name), feed)?;
+    }
+
+    Ok(())
+}
+
+/// Render an Atom feed with the new contributors provided. When there are no
+/// new contributors, the timestamp of the last contribution is used as the
+/// feed last update time.
+fn render_feed(
+    settings: &Settings,
+    last_contribution_ts: &str,
+    title: &str,
+    file_name: &str,
+    new_contributors: &[NewContributor],
+) -> Result<String> {
+    let base_url = settings.theme.base_url.trim_end_matches('/');
+    let feed_url = format!("{base_url}/{FEEDS_PATH}/{file_name}");
+    let updated = new_contributors.first().map_or(last_contribution_ts, |c| &c.first_contribution_ts);
+
+    let mut feed = String::new();
+    writeln!(feed, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(feed, r#"<feed xmlns="http://www.w3.org/2005/Atom">"#)?;
+    writeln!(feed, "  <id>{}</id>", escape_xml(&feed_url))?;
+    writeln!(feed, "  <title>{}</title>", escape_xml(title))?;
+    writeln!(feed, "  <updated>{updated}</updated>")?;
+    writeln!(feed, r#"  <link rel="self" href="{}" />"#, escape_xml(&feed_url))?;
+    writeln!(feed, r#"  <link href="{}/" />"#, escape_xml(base_url))?;
+    for c in new_contributors {
+        let card_url = escape_xml(&contributor_url(base_url, &c.login));
+        writeln!(feed, "  <entry>")?;
+        writeln!(feed, "    <id>{card_url}</id>")?;
+        writeln!(feed, "    <title>Welcome {}!</title>", c.login)?;
+        writeln!(feed, r#"    <link href="{card_url}" />"#)?;
+        writeln!(feed, "    <updated>{}</updated>", c.first_contribution_ts)?;
+        writeln!(feed, "    <author><name>{}</name></author>", c.login)?;
+        writeln!(
+            feed,
+            "    <summary>{} made their first contribution to {}</summary>",
+            c.login, c.first_contribution_repository
+        )?;
+        writeln!(feed, "  </entry>")?;
+    }
+    writeln!(feed, "</feed>")?;
+
+    Ok(feed)
+}
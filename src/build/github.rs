@@ -61,7 +61,7 @@ impl Collector {
         })
     }
 
-    /// Collect contributions (commits, issues, prs) from GitHub for each of
+    /// Collect contributions (commits, issues, prs, reviews) from GitHub for each of
     /// the repositories in the GitHub organizations provided.
     #[instrument(skip(self, settings))]
     pub(crate) async fn collect_contributions(&self, settings: &Settings) -> Result<()> {
@@ -87,8 +87,15 @@ impl Collector {
                 if let Err(err) = self.collect_commits(&owner, &repo).await {
                     warn!("error collecting commits for repository ({owner}/{repo}): {err:?}");
                 }
-                if let Err(err) = self.collect_issues_and_prs(&owner, &repo).await {
-                    warn!("error collecting issues and prs for repository ({owner}/{repo}): {err:?}");
+                match self.collect_issues_and_prs(&owner, &repo).await {
+                    Ok(updated_prs) => {
+                        if let Err(err) = self.collect_reviews(&owner, &repo, updated_prs).await {
+                            warn!("error collecting reviews for repository ({owner}/{repo}): {err:?}");
+                        }
+                    }
+                    Err(err) => {
+                        warn!("error collecting issues and prs for repository ({owner}/{repo}): {err:?}");
+                    }
                 }
             })
             .await;
@@ -184,9 +191,9 @@ impl Collector {
     }
 
     /// Collect and cache all issues and pull requests available since the last
-    /// one processed.
+    /// one processed, returning the numbers of the pull requests collected.
     #[instrument(skip(self))]
-    async fn collect_issues_and_prs(&self, owner: &str, repo: &str) -> Result<()> {
+    async fn collect_issues_and_prs(&self, owner: &str, repo: &str) -> Result<Vec<i64>> {
         trace!(owner, repo, "collecting issues and prs");
 
         // Setup temporary database in memory
@@ -194,9 +201,14 @@ impl Collector {
         tmp_db.execute(db::CREATE_ISSUE_TABLE, [])?;
         tmp_db.execute(db::CREATE_PULL_REQUEST_TABLE, [])?;
 
-        // Build first page url
+        // Build first page url (when some of the issues or pull requests
+        // cached don't have state information yet, we'll fetch all of them
+        // again once so that it can be backfilled)
+        let pending_state = self.pending_state(owner, repo)?;
         let mut url = format!("{API_BASE_URL}/repos/{owner}/{repo}/issues?state=all&per_page=100");
-        if let Some(ts) = self.last_timestamp(db::GET_LAST_ISSUE_OR_PULL_REQUEST_TS, &[&owner, &repo])? {
+        if !pending_state
+            && let Some(ts) = self.last_timestamp(db::GET_LAST_ISSUE_OR_PULL_REQUEST_TS, &[&owner, &repo])?
+        {
             write!(url, "&since={ts}")?;
         }
 
@@ -240,6 +252,75 @@ impl Collector {
         tmp_db.execute(&format!("attach '{}' as cache;", &self.cache_db_file), [])?;
         tmp_db.execute(db::COPY_ISSUES_TO_CACHE, [])?;
         tmp_db.execute(db::COPY_PULL_REQUESTS_TO_CACHE, [])?;
+        if pending_state {
+            tmp_db.execute(db::REGISTER_STATE_BACKFILL, [owner, repo])?;
+        }
+
+        // Get the numbers of the pull requests collected
+        let mut stmt = tmp_db.prepare(db::GET_PULL_REQUESTS_NUMBERS)?;
+        let prs = stmt.query_map([owner, repo], |row| row.get(0))?.collect::<Result<Vec<i64>, _>>()?;
+
+        trace!(owner, repo, "done!");
+        Ok(prs)
+    }
+
+    /// Collect and cache the reviews of the pull requests provided. Reviewing
+    /// a pull request updates it, so the ones collected since the last run
+    /// are the only ones that may have new reviews. When the reviews of the
+    /// repository haven't been backfilled yet, the reviews of all the pull
+    /// requests cached are collected once.
+    #[instrument(skip(self, updated_prs))]
+    async fn collect_reviews(&self, owner: &str, repo: &str, updated_prs: Vec<i64>) -> Result<()> {
+        trace!(owner, repo, "collecting reviews");
+
+        // Setup temporary database in memory
+        let tmp_db = duckdb::Connection::open_in_memory()?;
+        tmp_db.execute(db::CREATE_REVIEW_TABLE, [])?;
+
+        // Select the pull requests whose reviews will be collected
+        let pending_backfill = self.pending_reviews_backfill(owner, repo)?;
+        let prs = if pending_backfill {
+            self.cached_prs(owner, repo)?
+        } else {
+            updated_prs
+        };
+
+        // Fetch each pull request reviews pages until there are no more
+        // available
+        for number in prs {
+            let mut url = format!("{API_BASE_URL}/repos/{owner}/{repo}/pulls/{number}/reviews?per_page=100");
+            loop {
+                // Fetch page
+                let (headers, Some(body)) = self.fetch_page(&url).await? else {
+                    break;
+                };
+
+                // Load page reviews into temporary database
+                tmp_db.execute(
+                    db::LOAD_REVIEWS_FROM_JSON_FILE,
+                    duckdb::params![
+                        owner,
+                        repo,
+                        number,
+                        body.path().to_str().expect("path to be valid unicode"),
+                    ],
+                )?;
+
+                // Get next page url
+                let Some(next_page_url) = Self::next_page(&headers)? else {
+                    break;
+                };
+                url = next_page_url;
+            }
+        }
+
+        // Copy reviews collected from temporary database to cache database
+        let _cache_guard = self.cache_lock.lock().unwrap();
+        tmp_db.execute(&format!("attach '{}' as cache;", &self.cache_db_file), [])?;
+        tmp_db.execute(db::COPY_REVIEWS_TO_CACHE, [])?;
+        if pending_backfill {
+            tmp_db.execute(db::REGISTER_REVIEWS_BACKFILL, [owner, repo])?;
+        }
 
         trace!(owner, repo, "done!");
         Ok(())
@@ -306,6 +387,57 @@ impl Collector {
         Ok(ts)
     }
 
+    /// Check if some of the issues or pull requests cached for the provided
+    /// repository don't have state information yet (and they haven't been
+    /// backfilled already).
+    #[instrument(skip(self), err)]
+    fn pending_state(&self, owner: &str, repo: &str) -> Result<bool> {
+        // Open read-only connection to cache database
+        let db = duckdb::Connection::open_with_flags(
+            &self.cache_db_file,
+            Config::default().access_mode(AccessMode::ReadOnly)?,
+        )?;
+
+        let pending = db.query_row(
+            db::HAS_ISSUES_OR_PULL_REQUESTS_WITHOUT_STATE,
+            [owner, repo],
+            |row| row.get(0),
+        )?;
+
+        Ok(pending)
+    }
+
+    /// Check if the reviews of the pull requests cached for the provided
+    /// repository haven't been backfilled yet.
+    #[instrument(skip(self), err)]
+    fn pending_reviews_backfill(&self, owner: &str, repo: &str) -> Result<bool> {
+        // Open read-only connection to cache database
+        let db = duckdb::Connection::open_with_flags(
+            &self.cache_db_file,
+            Config::default().access_mode(AccessMode::ReadOnly)?,
+        )?;
+
+        let backfilled: bool = db.query_row(db::HAS_REVIEWS_BACKFILL, [owner, repo], |row| row.get(0))?;
+
+        Ok(!backfilled)
+    }
+
+    /// Return the numbers of the pull requests cached for the provided
+    /// repository.
+    #[instrument(skip(self), err)]
+    fn cached_prs(&self, owner: &str, repo: &str) -> Result<Vec<i64>> {
+        // Open read-only connection to cache database
+        let db = duckdb::Connection::open_with_flags(
+            &self.cache_db_file,
+            Config::default().access_mode(AccessMode::ReadOnly)?,
+        )?;
+
+        let mut stmt = db.prepare(db::GET_PULL_REQUESTS_NUMBERS)?;
+        let prs = stmt.query_map([owner, repo], |row| row.get(0))?.collect::<Result<Vec<i64>, _>>()?;
+
+        Ok(prs)
+    }
+
     /// Return the next page url from the information in the link header.
     #[instrument(err)]
     fn next_page(headers: &HeaderMap) -> Result<Option<String>> {
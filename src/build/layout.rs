@@ -0,0 +1,71 @@
+//! This module defines the layout used to organize the contributors data
+//! files in the data directory.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::build::{DATA_PATH, output::Output, settings::DataLayoutSettings};
+
+/// File name of the layout descriptor (written to the data directory).
+pub(crate) const LAYOUT_FILE: &str = "_layout.json";
+
+/// Current version of the data files layout.
+const LAYOUT_VERSION: u32 = 1;
+
+/// Character used to pad the shards of logins shorter than the shard depth.
+const PADDING_CHAR: char = '_';
+
+/// Layout of the contributors data files.
+///
+/// Data files are sharded into nested subdirectories named after the first
+/// characters (lowercased) of the contributor's login, so a shard depth of 2
+/// places `contributor.json` at `c/o/contributor.json`. A depth of 0 keeps
+/// all data files in the data directory itself.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DataLayout {
+    pub version: u32,
+    pub shard_depth: usize,
+}
+
+impl DataLayout {
+    /// Create a new data layout instance from the settings provided.
+    pub(crate) fn new(settings: &DataLayoutSettings) -> Self {
+        Self {
+            version: LAYOUT_VERSION,
+            shard_depth: settings.shard_depth,
+        }
+    }
+
+    /// Read the layout descriptor from the data directory provided, if any.
+    pub(crate) fn read(data_dir: &Path) -> Result<Option<Self>> {
+        let path = data_dir.join(LAYOUT_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let layout = serde_json::from_slice(&fs::read(path)?)?;
+
+        Ok(Some(layout))
+    }
+
+    /// Write the layout descriptor to the data directory.
+    pub(crate) fn write(&self, output: &mut Output) -> Result<()> {
+        output.write(Path::new(DATA_PATH).join(LAYOUT_FILE), serde_json::to_vec(self)?)
+    }
+
+    /// Return the path of the data file of the contributor provided, relative
+    /// to the data directory.
+    pub(crate) fn contributor_path(&self, login: &str) -> PathBuf {
+        let mut path = PathBuf::new();
+        let mut chars = login.chars().flat_map(char::to_lowercase);
+        for _ in 0..self.shard_depth {
+            path.push(chars.next().unwrap_or(PADDING_CHAR).to_string());
+        }
+        path.push(format!("{login}.json"));
+        path
+    }
+}
@@ -0,0 +1,94 @@
+//! This module is in charge of generating the leaderboards data files.
+
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use serde::Serialize;
+use tracing::{debug, instrument};
+
+use crate::build::{DATA_PATH, db, output::Output, settings::LeaderboardsSettings};
+
+/// Path where the leaderboards data files will be written to in the data
+/// directory.
+const LEADERBOARDS_PATH: &str = "_leaderboards";
+
+/// Index of the leaderboards available.
+#[derive(Debug, Clone, Default, Serialize)]
+struct LeaderboardsIndex {
+    years: Vec<String>,
+    repositories: Vec<String>,
+    projects: Vec<String>,
+}
+
+/// Generate leaderboards data files (all time, per year, per repository and
+/// per project), as well as an index listing all of them.
+#[instrument(skip_all, err)]
+pub(crate) fn generate_leaderboards_data_files(
+    output: &mut Output,
+    contribs_db: &duckdb::Connection,
+    settings: &LeaderboardsSettings,
+) -> Result<()> {
+    if !settings.enabled {
+        return Ok(());
+    }
+
+    debug!("generating leaderboards data files");
+
+    // Prepare contributions to take into account in the leaderboards
+    let kinds = settings.kinds.iter().map(|kind| kind.as_str()).collect::<Vec<_>>().join(",");
+    let bots_pattern = if settings.exclude_bots {
+        settings.bots_pattern.as_str()
+    } else {
+        ""
+    };
+    let excluded_logins = settings
+        .excluded_logins
+        .iter()
+        .map(|login| login.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(",");
+    contribs_db.execute(
+        db::PREPARE_LEADERBOARD_CONTRIBUTION_TABLE,
+        [kinds.as_str(), bots_pattern, excluded_logins.as_str()],
+    )?;
+    contribs_db.execute(db::CREATE_LEADERBOARD_CONTRIBUTOR_TABLE, [])?;
+
+    // Get leaderboards from database
+    let mut stmt = contribs_db.prepare(db::GET_LEADERBOARDS)?;
+    let rows = stmt.query_map([settings.size], |row| {
+        let scope: String = row.get(0)?;
+        let name: String = row.get(1)?;
+        let leaderboard: String = row.get(2)?;
+        Ok((scope, name, leaderboard))
+    })?;
+
+    // Write each of them to a file
+    let leaderboards_path = Path::new(DATA_PATH).join(LEADERBOARDS_PATH);
+    let mut index = LeaderboardsIndex::default();
+    for row in rows {
+        let (scope, name, leaderboard) = row?;
+        let file_path = match scope.as_str() {
+            "all" => leaderboards_path.join("all.json"),
+            "year" => {
+                index.years.push(name.clone());
+                leaderboards_path.join(format!("{name}.json"))
+            }
+            "repository" => {
+                index.repositories.push(name.clone());
+                leaderboards_path.join("repositories").join(format!("{name}.json"))
+            }
+            "project" => {
+                index.projects.push(name.clone());
+                leaderboards_path.join("projects").join(format!("{name}.json"))
+            }
+            _ => bail!("unexpected leaderboard scope: {scope}"),
+        };
+        output.write(file_path, leaderboard)?;
+    }
+
+    // Write leaderboards index file
+    index.years.reverse();
+    output.write(leaderboards_path.join("_index.json"), serde_json::to_vec(&index)?)?;
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//! This module defines the manifest listing the files generated by the build.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io,
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// File name of the manifest (written to the output directory).
+pub(crate) const MANIFEST_FILE: &str = "manifest.json";
+
+/// Manifest of the files generated by the build.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Manifest {
+    /// Version of the contribcard tool used to generate the files.
+    pub version: String,
+    /// Date the build was completed (RFC3339).
+    pub created_at: String,
+    /// SHA-256 digest of the settings used in the build.
+    pub settings_digest: String,
+    /// Files generated, keyed by their path relative to the output directory.
+    pub files: BTreeMap<String, ManifestEntry>,
+}
+
+/// Manifest entry describing a file generated by the build.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ManifestEntry {
+    pub size: u64,
+    pub md5: String,
+    pub sha256: String,
+    pub content_type: String,
+    /// Encoding of the content, for precompressed variants of other files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_encoding: Option<String>,
+}
+
+impl Manifest {
+    /// Read the manifest from the output directory provided, if any.
+    pub(crate) fn read(output_dir: &Path) -> Result<Option<Self>> {
+        let path = output_dir.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let manifest = serde_json::from_slice(&fs::read(path)?).context("error reading manifest")?;
+
+        Ok(Some(manifest))
+    }
+}
+
+impl ManifestEntry {
+    /// Create a new manifest entry for the file at the path provided (relative
+    /// to the output directory) with the content given.
+    pub(crate) fn new(path: &str, content: &[u8]) -> Self {
+        Self {
+            size: content.len() as u64,
+            md5: md5sum(content),
+            sha256: sha256sum(content),
+            content_type: mime_guess::from_path(path).first_or_octet_stream().essence_str().to_string(),
+            content_encoding: None,
+        }
+    }
+}
+
+/// Calculate the MD5 digest of the content provided.
+fn md5sum(content: &[u8]) -> String {
+    format!("{:x}", Md5::digest(content))
+}
+
+/// Calculate the MD5 digest of the file provided (reading it in chunks, so
+/// that the whole file isn't loaded in memory).
+pub(crate) fn md5sum_file(path: &Path) -> Result<String> {
+    let mut hasher = Md5::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Calculate the SHA-256 digest of the content provided.
+pub(crate) fn sha256sum(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
@@ -0,0 +1,165 @@
+//! This module defines the milestones engine, in charge of computing the
+//! milestones achieved by each contributor from the rules provided.
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+use chrono::{DateTime, Months};
+use tracing::{debug, instrument};
+
+use crate::build::{
+    db,
+    settings::{ContributionKind, MilestoneCondition, MilestoneRule, MilestonesSettings},
+};
+
+/// Compute the milestones achieved by each contributor and store them in the
+/// milestone table, so that they can be included in the contributors data
+/// files.
+#[instrument(skip_all, err)]
+pub(crate) fn compute_milestones(
+    contribs_db: &duckdb::Connection,
+    settings: &MilestonesSettings,
+) -> Result<()> {
+    debug!("computing milestones");
+
+    // Prepare rules to apply
+    let rules = settings.rules();
+    if rules.is_empty() {
+        return Ok(());
+    }
+
+    // Walk all contributions (sorted by author) and process them in batches
+    // containing all the contributions of a given contributor
+    let mut appender = contribs_db.appender("milestone")?;
+    let mut stmt = contribs_db.prepare(db::GET_ALL_CONTRIBUTIONS_BY_AUTHOR)?;
+    let mut rows = stmt.query([])?;
+    let mut author_id = None;
+    let mut contributions = vec![];
+    while let Some(row) = rows.next()? {
+        let contribution = Contribution {
+            author_id: row.get(0)?,
+            kind: row.get(1)?,
+            owner: row.get(2)?,
+            repository: row.get(3)?,
+            sha: row.get(4)?,
+            number: row.get(5)?,
+            title: row.get(6)?,
+            ts: row.get(7)?,
+            merged_at: row.get(8)?,
+            state_reason: row.get(9)?,
+        };
+        if author_id.is_some_and(|id| id != contribution.author_id) {
+            append_milestones(&mut appender, &rules, &contributions)?;
+            contributions.clear();
+        }
+        author_id = Some(contribution.author_id);
+        contributions.push(contribution);
+    }
+    if !contributions.is_empty() {
+        append_milestones(&mut appender, &rules, &contributions)?;
+    }
+    appender.flush()?;
+
+    Ok(())
+}
+
+/// Append the milestones achieved in the contributions provided (all from
+/// the same contributor, sorted by timestamp) to the milestone table.
+fn append_milestones(
+    appender: &mut duckdb::Appender,
+    rules: &[MilestoneRule],
+    contributions: &[Contribution],
+) -> Result<()> {
+    for rule in rules {
+        let Some((ts, contribution)) = rule.condition.achieved(contributions) else {
+            continue;
+        };
+        appender.append_row(duckdb::params![
+            contribution.author_id,
+            rule.id,
+            rule.title,
+            ts,
+            contribution.kind,
+            contribution.owner,
+            contribution.repository,
+            contribution.sha,
+            contribution.number,
+            contribution.title,
+            contribution.ts,
+        ])?;
+    }
+
+    Ok(())
+}
+
+impl MilestoneCondition {
+    /// Check if the condition is met by the contributions provided (sorted by
+    /// timestamp), returning when it was achieved and the contribution that
+    /// triggered it.
+    fn achieved<'a>(&self, contributions: &'a [Contribution]) -> Option<(i64, &'a Contribution)> {
+        match self {
+            MilestoneCondition::Contributions { kind, count } => {
+                let mut matching: Vec<(i64, &Contribution)> = contributions
+                    .iter()
+                    .filter_map(|c| match kind {
+                        None => (!c.is_review()).then_some((c.ts, c)),
+                        Some(kind) => c.matches(*kind),
+                    })
+                    .collect();
+                matching.sort_by_key(|(ts, _)| *ts);
+                matching.get(count.checked_sub(1)?).copied()
+            }
+            MilestoneCondition::Repositories { count } => {
+                let mut repositories = HashSet::new();
+                contributions.iter().filter(|c| !c.is_review()).find_map(|c| {
+                    repositories.insert((c.owner.as_str(), c.repository.as_str()));
+                    (repositories.len() == *count).then_some((c.ts, c))
+                })
+            }
+            MilestoneCondition::Tenure { years } => {
+                let mut contributions = contributions.iter().filter(|c| !c.is_review());
+                let first = DateTime::from_timestamp(contributions.clone().next()?.ts, 0)?;
+                let anniversary = first.checked_add_months(Months::new(years.checked_mul(12)?))?.timestamp();
+                contributions.find(|c| c.ts >= anniversary).map(|c| (c.ts, c))
+            }
+        }
+    }
+}
+
+/// Contribution details used to evaluate the milestones rules.
+#[derive(Debug, Clone)]
+struct Contribution {
+    author_id: i64,
+    kind: String,
+    owner: String,
+    repository: String,
+    sha: Option<String>,
+    number: Option<i64>,
+    title: Option<String>,
+    ts: i64,
+    merged_at: Option<i64>,
+    state_reason: Option<String>,
+}
+
+impl Contribution {
+    /// Check if the contribution is a review. Reviews are only taken into
+    /// account by the conditions that explicitly target them.
+    fn is_review(&self) -> bool {
+        self.kind == "review"
+    }
+
+    /// Return the timestamp at which the contribution counts as the kind
+    /// provided, if it does.
+    fn matches(&self, kind: ContributionKind) -> Option<(i64, &Self)> {
+        match kind {
+            ContributionKind::Commit => (self.kind == "commit").then_some((self.ts, self)),
+            ContributionKind::Issue => (self.kind == "issue").then_some((self.ts, self)),
+            ContributionKind::IssueCompleted => (self.kind == "issue"
+                && self.state_reason.as_deref() == Some("completed"))
+            .then_some((self.ts, self)),
+            ContributionKind::PullRequest => (self.kind == "pull_request").then_some((self.ts, self)),
+            ContributionKind::PullRequestMerged => self.merged_at.map(|merged_at| (merged_at, self)),
+            ContributionKind::Review => self.is_review().then_some((self.ts, self)),
+        }
+    }
+}
@@ -1,33 +1,64 @@
 //! This module defines the functionality of the build CLI subcommand.
 
 use std::{
-    fs::{self, File},
-    io::Write,
+    collections::HashSet,
+    fs,
     path::{Path, PathBuf},
     time::Instant,
 };
 
 use anyhow::{Context, Result, bail};
 use askama::Template;
+use chrono::{DateTime, Utc};
 use reqwest::{StatusCode, Url};
 use rust_embed::RustEmbed;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
 
 use crate::{
     BuildArgs,
-    build::settings::{Settings, Theme},
+    build::{
+        layout::DataLayout,
+        output::Output,
+        settings::{Project, RankingSettings, Settings, Theme},
+    },
 };
 
+mod badges;
 mod db;
+mod feeds;
 mod github;
-mod settings;
+pub(crate) mod layout;
+mod leaderboards;
+pub(crate) mod manifest;
+mod milestones;
+mod og_images;
+mod output;
+mod search;
+pub(crate) mod settings;
+mod sitemap;
+mod wrapped;
 
 /// Path where the data files will be written to in the output directory.
-const DATA_PATH: &str = "data";
+pub(crate) const DATA_PATH: &str = "data";
 
 /// Path where some images will be written to in the output directory.
 const IMAGES_PATH: &str = "images";
 
+/// Paths used at the top level of the output directory (in addition to the
+/// ones used by the web application assets) or of the deployed website (i.e.
+/// the releases in versioned S3 deploys or the custom domain file in git
+/// deploys). They are compared case-insensitively.
+const RESERVED_PATHS: &[&str] = &[
+    badges::BADGES_PATH,
+    "CNAME",
+    DATA_PATH,
+    feeds::FEEDS_PATH,
+    IMAGES_PATH,
+    "releases",
+    sitemap::SITEMAPS_PATH,
+    wrapped::WRAPPED_PATH,
+];
+
 /// Embed web application assets into binary.
 /// (these assets will be built automatically from the build script)
 #[derive(RustEmbed)]
@@ -45,7 +76,8 @@ pub(crate) async fn build(args: &BuildArgs) -> Result<()> {
     let cache_dir = setup_cache_dir(args.cache_dir.as_ref())?;
     let base_cache_db = BaseCacheDB::new(args);
     let cache_db_file = setup_cache_db(&cache_dir, &args.name, base_cache_db.as_ref()).await?;
-    setup_output_dir(&args.output_dir)?;
+    let settings_digest = manifest::sha256sum(&serde_json::to_vec(&settings)?);
+    let mut output = Output::new(&args.output_dir, settings_digest, &settings.compression)?;
 
     // Collect contributions from GitHub
     if args.collect_contributions.unwrap_or(true) {
@@ -53,21 +85,76 @@ pub(crate) async fn build(args: &BuildArgs) -> Result<()> {
         collector.collect_contributions(&settings).await?;
     }
     let contribs_db = prepare_contributions_table(&cache_db_file)?;
+    prepare_projects_table(&contribs_db, &settings.projects)?;
+
+    // Compute contributors rankings
+    compute_rankings(&contribs_db, &settings.ranking)?;
+
+    // Compute milestones achieved by contributors
+    milestones::compute_milestones(&contribs_db, &settings.milestones)?;
 
     // Generate contributors data files
-    generate_contributors_data_files(&args.output_dir, &contribs_db)?;
+    let layout = DataLayout::new(&settings.data_layout);
+    generate_contributors_data_files(&mut output, &contribs_db, &layout)?;
+
+    // Generate search index data files
+    search::generate_search_index(&mut output, &contribs_db, &settings.search)?;
+
+    // Generate stats data file
+    generate_stats_data_file(&mut output, &contribs_db)?;
+
+    // Generate leaderboards data files
+    leaderboards::generate_leaderboards_data_files(&mut output, &contribs_db, &settings.leaderboards)?;
+
+    // Generate year in review data files and pages (if enabled)
+    let features = Features::new(&settings);
+    if let Some(wrapped_settings) = &settings.wrapped {
+        wrapped::generate_wrapped(
+            &mut output,
+            &contribs_db,
+            wrapped_settings,
+            &settings.theme,
+            features,
+        )?;
+    }
 
-    // Generate all contributors data file
-    generate_all_contributors_data_file(&args.output_dir, &contribs_db)?;
+    // Generate contributors and repositories badges (if enabled)
+    if let Some(badge_theme) = &settings.theme.badge {
+        badges::generate_badges(&mut output, &contribs_db, badge_theme)?;
+    }
 
     // Render index file and write it to the output directory
-    render_index(&settings.theme, &args.output_dir)?;
+    render_index(&settings.theme, features, &mut output)?;
+
+    // Render contributors pages and write them to the output directory
+    render_contributors_pages(&settings.theme, features, &mut output, &contribs_db)?;
+
+    // Generate new contributors feeds
+    feeds::generate_feeds(&mut output, &contribs_db, &settings)?;
+
+    // Generate sitemap and robots.txt files
+    sitemap::generate_sitemap(&mut output, &contribs_db, &settings)?;
 
     // Download and copy theme images to the output directory
-    copy_theme_images(&settings.theme, &args.output_dir).await?;
+    copy_theme_images(&settings.theme, &mut output).await?;
+
+    // Render contributors Open Graph images (if enabled)
+    if settings.theme.og_images {
+        og_images::render_og_images(&mut output, &contribs_db, &settings.theme)?;
+    }
 
     // Copy web assets files to the output directory
-    copy_web_assets(&args.output_dir)?;
+    copy_web_assets(&mut output)?;
+
+    // Remove stale files from the output directory and write manifest
+    let stats = output.finish()?;
+    info!(
+        added = stats.added,
+        updated = stats.updated,
+        removed = stats.removed,
+        unchanged = stats.unchanged,
+        "output files written"
+    );
 
     let duration = start.elapsed().as_secs_f64();
     info!("contribcard website built! (took: {:.3}s)", duration);
@@ -76,9 +163,9 @@ pub(crate) async fn build(args: &BuildArgs) -> Result<()> {
 
 /// Copy theme images to the output directory.
 #[instrument(skip(theme), err)]
-async fn copy_theme_images(theme: &Theme, output_dir: &Path) -> Result<()> {
+async fn copy_theme_images(theme: &Theme, output: &mut Output) -> Result<()> {
     // Helper function to download an image to the output directory
-    async fn download_image(url: &str, output_dir: &Path) -> Result<()> {
+    async fn download_image(url: &str, output: &mut Output) -> Result<()> {
         // Fetch image
         let resp = reqwest::get(url).await.context(format!("error downloading image ({url})"))?;
         if resp.status() != StatusCode::OK {
@@ -90,28 +177,24 @@ async fn copy_theme_images(theme: &Theme, output_dir: &Path) -> Result<()> {
         let img = resp.bytes().await?;
 
         // Write image to output dir
-        let url = Url::parse(url).context("invalid image url")?;
-        let Some(file_name) = url.path_segments().and_then(Iterator::last) else {
-            bail!("invalid image url: {url}");
-        };
-        let img_path = Path::new(IMAGES_PATH).join(file_name);
-        File::create(output_dir.join(&img_path))?.write_all(&img)?;
+        let img_path = Path::new(IMAGES_PATH).join(file_name(url)?);
+        output.write(img_path, img)?;
 
         Ok(())
     }
 
     debug!("copying theme images to output directory");
 
-    download_image(&theme.favicon_url, output_dir).await.context("favicon")?;
-    download_image(&theme.logo_url, output_dir).await.context("logo")?;
-    download_image(&theme.og_image_url, output_dir).await.context("og_image")?;
+    download_image(&theme.favicon_url, output).await.context("favicon")?;
+    download_image(&theme.logo_url, output).await.context("logo")?;
+    download_image(&theme.og_image_url, output).await.context("og_image")?;
 
     Ok(())
 }
 
 /// Copy web assets files to the output directory.
-#[instrument(err)]
-fn copy_web_assets(output_dir: &Path) -> Result<()> {
+#[instrument(skip_all, err)]
+fn copy_web_assets(output: &mut Output) -> Result<()> {
     debug!("copying web assets to output directory");
 
     for asset_path in WebAssets::iter() {
@@ -122,11 +205,7 @@ fn copy_web_assets(output_dir: &Path) -> Result<()> {
         }
 
         if let Some(embedded_file) = WebAssets::get(&asset_path) {
-            if let Some(parent_path) = Path::new(asset_path.as_ref()).parent() {
-                fs::create_dir_all(output_dir.join(parent_path))?;
-            }
-            let mut file = File::create(output_dir.join(asset_path.as_ref()))?;
-            file.write_all(&embedded_file.data)?;
+            output.write(asset_path.as_ref(), &embedded_file.data)?;
         }
     }
 
@@ -135,7 +214,11 @@ fn copy_web_assets(output_dir: &Path) -> Result<()> {
 
 /// Generate contributors data files.
 #[instrument(skip_all, err)]
-fn generate_contributors_data_files(output_dir: &Path, contribs_db: &duckdb::Connection) -> Result<()> {
+fn generate_contributors_data_files(
+    output: &mut Output,
+    contribs_db: &duckdb::Connection,
+    layout: &DataLayout,
+) -> Result<()> {
     debug!("generating contributors data files");
 
     // Get all contributors summaries from database
@@ -146,29 +229,30 @@ fn generate_contributors_data_files(output_dir: &Path, contribs_db: &duckdb::Con
         Ok((user, summary))
     })?;
 
-    // Write each of them to a file
-    let data_path = output_dir.join(DATA_PATH);
+    // Write each of them to a file (following the layout provided)
+    let data_path = Path::new(DATA_PATH);
     for row in rows {
         let Ok((user, summary)) = row else { continue };
-        let mut file = File::create(data_path.join(format!("{user}.json")))?;
-        file.write_all(summary.as_bytes())?;
+        output.write(data_path.join(layout.contributor_path(&user)), summary)?;
     }
 
+    // Write layout descriptor
+    layout.write(output)?;
+
     Ok(())
 }
 
-/// Generate all contributors data file.
+/// Generate stats data file.
 #[instrument(skip_all, err)]
-fn generate_all_contributors_data_file(output_dir: &Path, contribs_db: &duckdb::Connection) -> Result<()> {
-    debug!("generating all contributors data file");
+fn generate_stats_data_file(output: &mut Output, contribs_db: &duckdb::Connection) -> Result<()> {
+    debug!("generating stats data file");
 
-    // Get contributors from cache database
-    let contributors: String = contribs_db.query_row(db::GET_CONTRIBUTORS, [], |row| row.get(0))?;
+    // Get stats from database
+    let today = Utc::now().date_naive().to_string();
+    let stats: String = contribs_db.query_row(db::GET_STATS, [today], |row| row.get(0))?;
 
-    // Write contributors data file to the output directory
-    let data_path = output_dir.join(DATA_PATH);
-    let mut file = File::create(data_path.join("_all_contributors.json"))?;
-    file.write_all(contributors.as_bytes())?;
+    // Write stats data file to the output directory
+    output.write(Path::new(DATA_PATH).join("_stats.json"), stats)?;
 
     Ok(())
 }
@@ -182,30 +266,219 @@ fn prepare_contributions_table(cache_db_file: &str) -> Result<duckdb::Connection
     let contribs_db = duckdb::Connection::open_in_memory()?;
     contribs_db.execute(&format!("attach '{}' as cache;", &cache_db_file), [])?;
     contribs_db.execute(db::CREATE_CONTRIBUTION_TABLE, [])?;
+    contribs_db.execute(db::CREATE_MILESTONE_TABLE, [])?;
+    contribs_db.execute(db::CREATE_PROJECT_REPOSITORY_TABLE, [])?;
+    contribs_db.execute(db::CREATE_RANKING_TABLE, [])?;
     contribs_db.execute_batch(db::LOAD_CONTRIBUTIONS_FROM_CACHE)?;
 
     Ok(contribs_db)
 }
 
+/// Prepare projects table from the projects defined in the settings.
+#[instrument(skip_all, err)]
+fn prepare_projects_table(contribs_db: &duckdb::Connection, projects: &[Project]) -> Result<()> {
+    debug!("preparing projects table");
+
+    let mut appender = contribs_db.appender("project_repository")?;
+    for project in projects {
+        for repo in &project.repositories {
+            let Some((owner, repository)) = repo.split_once('/') else {
+                bail!("project repository format must be owner/repo, found: {repo}");
+            };
+            appender.append_row([project.name.as_str(), owner, repository])?;
+        }
+    }
+    appender.flush()?;
+    contribs_db.execute_batch(db::RESOLVE_PROJECT_REPOSITORIES)?;
+
+    Ok(())
+}
+
+/// Compute contributors rankings, discarding the ones that don't meet the
+/// thresholds configured.
+#[instrument(skip_all, err)]
+fn compute_rankings(contribs_db: &duckdb::Connection, settings: &RankingSettings) -> Result<()> {
+    debug!("computing rankings");
+
+    contribs_db.execute_batch(db::COMPUTE_RANKINGS)?;
+    contribs_db.execute(
+        db::DELETE_RANKINGS_BELOW_THRESHOLDS,
+        duckdb::params![settings.max_top_percent, settings.min_contributions],
+    )?;
+
+    Ok(())
+}
+
 /// Template for the index document.
 #[derive(Debug, Clone, Template)]
 #[template(path = "index.html", escape = "none")]
 struct Index<'a> {
+    features: Features,
+    meta: Meta,
     theme: &'a Theme,
 }
 
+/// Optional features enabled in the site, so that the web application can
+/// adjust its content accordingly.
+#[derive(Debug, Clone, Copy)]
+struct Features {
+    feeds: bool,
+    leaderboards: bool,
+}
+
+impl Features {
+    /// Create a new Features instance from the settings provided.
+    fn new(settings: &Settings) -> Self {
+        Self {
+            feeds: settings.feeds.enabled,
+            leaderboards: settings.leaderboards.enabled,
+        }
+    }
+}
+
+/// Metadata of the page rendered using the index document template.
+#[derive(Debug, Clone)]
+struct Meta {
+    title: String,
+    description: String,
+    url: String,
+    image_url: String,
+}
+
+impl Meta {
+    /// Create a new Meta instance using the default values from the theme.
+    fn new(theme: &Theme) -> Result<Self> {
+        Ok(Self {
+            title: theme.og_title.clone(),
+            description: theme.og_description.clone(),
+            url: theme.base_url.clone(),
+            image_url: format!(
+                "{}/{IMAGES_PATH}/{}",
+                theme.base_url.trim_end_matches('/'),
+                file_name(&theme.og_image_url)?
+            ),
+        })
+    }
+}
+
 /// Render index file and write it to the output directory.
 #[instrument(skip_all, err)]
-fn render_index(theme: &Theme, output_dir: &Path) -> Result<()> {
+fn render_index(theme: &Theme, features: Features, output: &mut Output) -> Result<()> {
     debug!("rendering index.html file");
 
     // Prepare index, render it and write it to output dir
-    let index = Index { theme }.render()?;
-    File::create(output_dir.join("index.html"))?.write_all(index.as_bytes())?;
+    let meta = Meta::new(theme)?;
+    let index = Index {
+        features,
+        meta,
+        theme,
+    }
+    .render()?;
+    output.write("index.html", index)?;
+
+    Ok(())
+}
+
+/// Template for the contributor pages.
+#[derive(Debug, Clone, Template)]
+#[template(path = "contributor.html", escape = "none")]
+struct ContributorPage<'a> {
+    features: Features,
+    login: &'a str,
+    meta: Meta,
+    theme: &'a Theme,
+}
+
+/// Render a page for each of the contributors and write them to the output
+/// directory. These pages include some contributor specific metadata, so that
+/// link previews and crawlers that don't run the web application can use it.
+#[instrument(skip_all, err)]
+fn render_contributors_pages(
+    theme: &Theme,
+    features: Features,
+    output: &mut Output,
+    contribs_db: &duckdb::Connection,
+) -> Result<()> {
+    debug!("rendering contributors pages");
+
+    let default_meta = Meta::new(theme)?;
+    let reserved_paths = reserved_paths();
+    for info in get_contributors_cards_info(contribs_db)? {
+        // Skip contributors whose login clashes with a reserved path
+        let login = info.login.as_str();
+        if reserved_paths.contains(&login.to_lowercase()) {
+            warn!(
+                login,
+                "login clashes with a reserved path, skipping contributor page"
+            );
+            continue;
+        }
+
+        // Prepare contributor page metadata
+        let first_contribution_date = format_date(info.first_contribution_ts);
+        let meta = Meta {
+            title: format!("{login} | {}", theme.og_title),
+            description: format!(
+                "{} {} to {} {} since {first_contribution_date}",
+                prettify_number(info.total),
+                if info.total == 1 {
+                    "contribution"
+                } else {
+                    "contributions"
+                },
+                info.repositories,
+                if info.repositories == 1 {
+                    "repository"
+                } else {
+                    "repositories"
+                },
+            ),
+            url: contributor_url(&theme.base_url, login),
+            image_url: contributor_image_url(theme, login, &default_meta.image_url),
+        };
+
+        // Render page and write it to the output directory
+        let page = ContributorPage {
+            features,
+            login,
+            meta,
+            theme,
+        }
+        .render()?;
+        output.write(Path::new(login).join("index.html"), page)?;
+    }
 
     Ok(())
 }
 
+/// Information about a contributor displayed in their card previews.
+#[derive(Debug, Clone)]
+struct ContributorCardInfo {
+    login: String,
+    total: i64,
+    repositories: i64,
+    first_contribution_ts: i64,
+    first_contribution_repository: String,
+}
+
+/// Get the card information of all contributors from the database.
+fn get_contributors_cards_info(contribs_db: &duckdb::Connection) -> Result<Vec<ContributorCardInfo>> {
+    let mut stmt = contribs_db.prepare(db::GET_CONTRIBUTORS_CARDS_INFO)?;
+    let info = stmt
+        .query_map([], |row| {
+            Ok(ContributorCardInfo {
+                login: row.get(0)?,
+                total: row.get(1)?,
+                repositories: row.get(2)?,
+                first_contribution_ts: row.get(3)?,
+                first_contribution_repository: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(info)
+}
+
 /// Setup cache database.
 #[instrument(skip(base_db), err)]
 async fn setup_cache_db(cache_dir: &Path, name: &str, base_db: Option<&BaseCacheDB>) -> Result<String> {
@@ -237,6 +510,13 @@ async fn setup_cache_db(cache_dir: &Path, name: &str, base_db: Option<&BaseCache
     db.execute(db::CREATE_COMMIT_TABLE, [])?;
     db.execute(db::CREATE_ISSUE_TABLE, [])?;
     db.execute(db::CREATE_PULL_REQUEST_TABLE, [])?;
+    db.execute(db::CREATE_REVIEW_BACKFILL_TABLE, [])?;
+    db.execute(db::CREATE_REVIEW_TABLE, [])?;
+    db.execute(db::CREATE_STATE_BACKFILL_TABLE, [])?;
+
+    // Migrate tables created by previous versions if needed
+    db.execute_batch(db::MIGRATE_ISSUE_TABLE)?;
+    db.execute_batch(db::MIGRATE_PULL_REQUEST_TABLE)?;
 
     Ok(path.display().to_string())
 }
@@ -263,25 +543,79 @@ fn setup_cache_dir(cache_dir: Option<&PathBuf>) -> Result<PathBuf> {
     Ok(cache_dir)
 }
 
-/// Setup output directory, creating it as well as any of the other required
-/// paths inside it when needed.
-#[instrument(err)]
-fn setup_output_dir(output_dir: &Path) -> Result<()> {
-    debug!("setting up output directory");
-
-    if !output_dir.exists() {
-        debug!("creating output directory");
-        fs::create_dir_all(output_dir)?;
+/// Return the url of the Open Graph image of the contributor provided (the
+/// default one provided is used when contributors images are not rendered).
+fn contributor_image_url(theme: &Theme, login: &str, default_image_url: &str) -> String {
+    if theme.og_images {
+        format!(
+            "{}/{IMAGES_PATH}/{}/{login}.png",
+            theme.base_url.trim_end_matches('/'),
+            og_images::OG_IMAGES_PATH
+        )
+    } else {
+        default_image_url.to_string()
     }
+}
 
-    for path in &[DATA_PATH, IMAGES_PATH] {
-        let path = output_dir.join(path);
-        if !path.exists() {
-            fs::create_dir(path)?;
+/// Return the url of the page of the contributor provided. Contributors pages
+/// are written to `{login}/index.html`, so the url includes a trailing slash
+/// (this is the form servers redirect to).
+fn contributor_url(base_url: &str, login: &str) -> String {
+    format!("{}/{login}/", base_url.trim_end_matches('/'))
+}
+
+/// Escape the text provided so that it can be safely included in a XML
+/// document.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Return the file name of the url provided.
+fn file_name(url: &str) -> Result<String> {
+    let url = Url::parse(url).context("invalid url")?;
+    let Some(file_name) = url.path_segments().and_then(Iterator::last) else {
+        bail!("invalid url: {url}");
+    };
+
+    Ok(file_name.to_string())
+}
+
+/// Format the timestamp provided as a date (i.e. Jan 2, 2024).
+fn format_date(ts: i64) -> String {
+    DateTime::from_timestamp(ts, 0)
+        .map(|ts| ts.format("%b %-d, %Y").to_string())
+        .unwrap_or_default()
+}
+
+/// Return the paths used at the top level of the output directory (lowercase),
+/// including the ones used by the web application assets. Contributors whose
+/// login matches any of them don't get a page, as it'd clash with the content
+/// in that path.
+fn reserved_paths() -> HashSet<String> {
+    let mut paths: HashSet<String> = RESERVED_PATHS.iter().map(|path| path.to_lowercase()).collect();
+    for asset_path in WebAssets::iter() {
+        if let Some((dir, _)) = asset_path.split_once('/') {
+            paths.insert(dir.to_lowercase());
         }
     }
+    paths
+}
 
-    Ok(())
+/// Return a short human readable representation of the number provided (i.e.
+/// 1234 -> 1.2k).
+#[allow(clippy::cast_precision_loss)]
+fn prettify_number(n: i64) -> String {
+    let (value, symbol) = match n {
+        ..1_000 => return n.to_string(),
+        1_000..1_000_000 => (n as f64 / 1e3, "k"),
+        _ => (n as f64 / 1e6, "M"),
+    };
+    let value = format!("{value:.1}");
+
+    format!("{}{symbol}", value.trim_end_matches(".0"))
 }
 
 /// Base cache database configuration.
@@ -0,0 +1,108 @@
+//! This module is in charge of rendering the Open Graph image of each of the
+//! contributors.
+
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result, format_err};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use resvg::{tiny_skia, usvg};
+use tracing::{debug, instrument};
+
+use crate::build::{
+    ContributorCardInfo, IMAGES_PATH, escape_xml, file_name, format_date, get_contributors_cards_info,
+    output::Output, prettify_number, settings::Theme,
+};
+
+/// Path where the Open Graph images will be written to in the images
+/// directory.
+pub(crate) const OG_IMAGES_PATH: &str = "og";
+
+/// Width of the Open Graph images.
+const WIDTH: u32 = 1200;
+
+/// Height of the Open Graph images.
+const HEIGHT: u32 = 630;
+
+/// Font family used in the Open Graph images.
+const FONT_FAMILY: &str = "DejaVu Sans";
+
+/// Fonts bundled to render the Open Graph images.
+const FONTS: [&[u8]; 2] = [
+    include_bytes!("../../fonts/DejaVuSans.ttf"),
+    include_bytes!("../../fonts/DejaVuSans-Bold.ttf"),
+];
+
+/// Render the Open Graph image of each of the contributors.
+///
+/// The theme images are expected to have been copied to the output directory
+/// already, as the logo will be read from there.
+#[instrument(skip_all, err)]
+pub(crate) fn render_og_images(
+    output: &mut Output,
+    contribs_db: &duckdb::Connection,
+    theme: &Theme,
+) -> Result<()> {
+    debug!("rendering open graph images");
+
+    // Setup rendering options with the bundled fonts
+    let mut options = usvg::Options {
+        font_family: FONT_FAMILY.to_string(),
+        ..usvg::Options::default()
+    };
+    for font in FONTS {
+        options.fontdb_mut().load_font_data(font.to_vec());
+    }
+
+    // Prepare logo to embed it in the images
+    let logo_file_name = file_name(&theme.logo_url)?;
+    let logo =
+        fs::read(output.dir().join(IMAGES_PATH).join(&logo_file_name)).context("error reading logo")?;
+    let logo_mime = mime_guess::from_path(&logo_file_name).first_or_octet_stream();
+    let logo_href = format!("data:{};base64,{}", logo_mime.essence_str(), BASE64.encode(logo));
+
+    // Render the image of each contributor and write it to the output directory
+    let og_images_path = Path::new(IMAGES_PATH).join(OG_IMAGES_PATH);
+    for info in get_contributors_cards_info(contribs_db)? {
+        let svg = prepare_svg(&info, &logo_href);
+        let tree = usvg::Tree::from_str(&svg, &options)?;
+        let mut pixmap = tiny_skia::Pixmap::new(WIDTH, HEIGHT).ok_or(format_err!("invalid image size"))?;
+        resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+        output.write(
+            og_images_path.join(format!("{}.png", info.login)),
+            pixmap.encode_png()?,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Prepare the SVG document of the Open Graph image of a contributor.
+fn prepare_svg(info: &ContributorCardInfo, logo_href: &str) -> String {
+    let first_contribution_date = format_date(info.first_contribution_ts);
+    let contributions = if info.total == 1 {
+        "contribution"
+    } else {
+        "contributions"
+    };
+    let repositories = if info.repositories == 1 {
+        "repository"
+    } else {
+        "repositories"
+    };
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}">
+    <rect width="{WIDTH}" height="{HEIGHT}" fill="#ffffff" />
+    <image x="80" y="60" width="480" height="140" preserveAspectRatio="xMinYMid meet" href="{logo_href}" />
+    <text x="80" y="330" font-size="76" font-weight="bold" fill="#1e1e1e">{login}</text>
+    <text x="80" y="420" font-size="38" fill="#6c757d">{total} {contributions} to {repositories_count} {repositories}</text>
+    <text x="80" y="490" font-size="30" fill="#6c757d">First contribution: {first_contribution_date} · {first_contribution_repository}</text>
+    <rect y="{bar_y}" width="{WIDTH}" height="20" fill="#1e1e1e" />
+</svg>"##,
+        login = escape_xml(&info.login),
+        total = prettify_number(info.total),
+        repositories_count = info.repositories,
+        first_contribution_repository = escape_xml(&info.first_contribution_repository),
+        bar_y = HEIGHT - 20,
+    )
+}
@@ -0,0 +1,449 @@
+//! This module defines the writer used to write the files generated by the
+//! build to the output directory.
+
+use std::{
+    collections::BTreeSet,
+    fs::{self, File},
+    io::Write,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::Utc;
+use flate2::{Compression, write::GzEncoder};
+use tracing::{debug, instrument};
+use walkdir::WalkDir;
+
+use crate::build::{
+    manifest::{MANIFEST_FILE, Manifest, ManifestEntry, md5sum_file},
+    settings::CompressionSettings,
+};
+
+/// Minimum size of the files that will be precompressed (in bytes).
+const MIN_COMPRESSION_SIZE: usize = 1024;
+
+/// Content types of the files that will be precompressed.
+const COMPRESSIBLE_CONTENT_TYPES: [&str; 8] = [
+    "application/atom+xml",
+    "application/javascript",
+    "application/json",
+    "application/manifest+json",
+    "application/wasm",
+    "application/xml",
+    "image/svg+xml",
+    "image/x-icon",
+];
+
+/// Writer used to write files to the output directory.
+///
+/// Files whose content hasn't changed since the last build are not written
+/// again, and files written by the previous build that haven't been written
+/// by the current one are removed when the writer is finished. The files
+/// written are recorded in the build manifest. When there is no manifest of
+/// a previous build (i.e. output directory written by an older version), the
+/// files not written by the current build found in the directories it writes
+/// to are removed instead.
+///
+/// When enabled, precompressed variants of the compressible files are written
+/// next to them as well.
+#[derive(Debug)]
+pub(crate) struct Output {
+    dir: PathBuf,
+    encodings: Vec<Encoding>,
+    previous_manifest: Option<Manifest>,
+    manifest: Manifest,
+    stats: OutputStats,
+}
+
+/// Number of files added, updated, removed and unchanged by the build.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct OutputStats {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+}
+
+impl Output {
+    /// Create a new output writer instance, setting up the output directory
+    /// if needed.
+    #[instrument(err)]
+    pub(crate) fn new(
+        dir: &Path,
+        settings_digest: String,
+        compression: &CompressionSettings,
+    ) -> Result<Self> {
+        debug!("setting up output directory");
+
+        if !dir.exists() {
+            debug!("creating output directory");
+            fs::create_dir_all(dir)?;
+        }
+
+        // Load the manifest of the previous build (if any)
+        let previous_manifest = Manifest::read(dir)?;
+
+        // Prepare encodings of the precompressed variants
+        let mut encodings = vec![];
+        if compression.gzip {
+            encodings.push(Encoding::Gzip);
+        }
+        if compression.brotli {
+            encodings.push(Encoding::Brotli);
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            encodings,
+            previous_manifest,
+            manifest: Manifest {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                settings_digest,
+                ..Manifest::default()
+            },
+            stats: OutputStats::default(),
+        })
+    }
+
+    /// Return the output directory.
+    pub(crate) fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write the content provided to the path given (relative to the output
+    /// directory), unless the existing file already has the same content.
+    pub(crate) fn write(&mut self, path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> Result<()> {
+        let content = content.as_ref();
+        let key = relative_path(path.as_ref());
+        let entry = ManifestEntry::new(&key, content);
+        let content_type = entry.content_type.clone();
+        let compressible = is_compressible(&content_type, content);
+        let changed = self.write_file(key.clone(), content, entry)?;
+
+        // Write precompressed variants (if enabled)
+        if !compressible {
+            return Ok(());
+        }
+        for encoding in self.encodings.clone() {
+            let variant_key = format!("{key}.{}", encoding.extension());
+
+            // Keep the previous variant when the content hasn't changed (and
+            // the variant file hasn't been modified since it was written)
+            let variant_path = self.dir.join(&variant_key);
+            if !changed
+                && let Some(previous_entry) =
+                    self.previous_manifest.as_ref().and_then(|manifest| manifest.files.get(&variant_key))
+                && variant_path.exists()
+                && md5sum_file(&variant_path)? == previous_entry.md5
+            {
+                self.stats.unchanged += 1;
+                self.manifest.files.insert(variant_key, previous_entry.clone());
+                continue;
+            }
+
+            let compressed_content = encoding.compress(content)?;
+            let variant_entry = ManifestEntry {
+                content_type: content_type.clone(),
+                content_encoding: Some(encoding.name().to_string()),
+                ..ManifestEntry::new(&variant_key, &compressed_content)
+            };
+            self.write_file(variant_key, &compressed_content, variant_entry)?;
+        }
+
+        Ok(())
+    }
+
+    /// Return the files written by the previous build that haven't been
+    /// written by this one. When there is no manifest of the previous build,
+    /// the files not written by this build found in the directories it has
+    /// written to are returned.
+    fn stale_files(&self) -> Result<BTreeSet<String>> {
+        if let Some(previous_manifest) = &self.previous_manifest {
+            return Ok(previous_manifest
+                .files
+                .keys()
+                .filter(|key| !self.manifest.files.contains_key(*key))
+                .cloned()
+                .collect());
+        }
+
+        let mut stale_files = BTreeSet::new();
+        let dirs: BTreeSet<&str> = self
+            .manifest
+            .files
+            .keys()
+            .filter_map(|key| key.split_once('/'))
+            .map(|(dir, _)| dir)
+            .collect();
+        for dir in dirs {
+            for dir_entry in WalkDir::new(self.dir.join(dir)) {
+                let dir_entry = dir_entry?;
+                if !dir_entry.file_type().is_file() {
+                    continue;
+                }
+                let key = relative_path(dir_entry.path().strip_prefix(&self.dir)?);
+                if !self.manifest.files.contains_key(&key) {
+                    stale_files.insert(key);
+                }
+            }
+        }
+
+        Ok(stale_files)
+    }
+
+    /// Write the file provided (unless the existing one already has the same
+    /// content), recording it in the manifest. Returns whether the file was
+    /// written or not.
+    fn write_file(&mut self, key: String, content: &[u8], entry: ManifestEntry) -> Result<bool> {
+        let file_path = self.dir.join(&key);
+
+        // Check if the file needs to be written
+        let existing_checksum = if file_path.exists() {
+            Some(md5sum_file(&file_path)?)
+        } else {
+            None
+        };
+        let written = match existing_checksum {
+            Some(existing_checksum) if existing_checksum == entry.md5 => {
+                self.stats.unchanged += 1;
+                false
+            }
+            Some(_) => {
+                File::create(&file_path)?.write_all(content)?;
+                self.stats.updated += 1;
+                true
+            }
+            None => {
+                if let Some(parent) = file_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                File::create(&file_path)?.write_all(content)?;
+                self.stats.added += 1;
+                true
+            }
+        };
+
+        self.manifest.files.insert(key, entry);
+        Ok(written)
+    }
+
+    /// Remove the files written by the previous build that haven't been
+    /// written by this one and write the build manifest, returning the output
+    /// stats.
+    #[instrument(skip_all, err)]
+    pub(crate) fn finish(mut self) -> Result<OutputStats> {
+        // Remove stale files (and the directories left empty)
+        for key in self.stale_files()? {
+            let file_path = self.dir.join(&key);
+            if file_path.is_file() {
+                fs::remove_file(&file_path)?;
+                self.stats.removed += 1;
+                debug!(file = key, "stale file removed");
+            }
+            let mut parent = file_path.parent();
+            while let Some(dir) = parent {
+                if dir == self.dir || fs::remove_dir(dir).is_err() {
+                    break;
+                }
+                parent = dir.parent();
+            }
+        }
+
+        // Write manifest
+        self.manifest.created_at = Utc::now().to_rfc3339();
+        let manifest = serde_json::to_vec_pretty(&self.manifest)?;
+        File::create(self.dir.join(MANIFEST_FILE))?.write_all(&manifest)?;
+
+        Ok(self.stats)
+    }
+}
+
+/// Encoding used in the precompressed variants of the output files.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    /// Compress the content provided using this encoding.
+    fn compress(self, content: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Encoding::Brotli => {
+                let mut compressed_content = vec![];
+                {
+                    let mut writer = brotli::CompressorWriter::new(&mut compressed_content, 4096, 11, 22);
+                    writer.write_all(content)?;
+                }
+                Ok(compressed_content)
+            }
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(vec![], Compression::best());
+                encoder.write_all(content)?;
+                Ok(encoder.finish()?)
+            }
+        }
+    }
+
+    /// Return the extension of the files using this encoding.
+    fn extension(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gz",
+        }
+    }
+
+    /// Return the name of the encoding (as used in the Content-Encoding
+    /// header).
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+        }
+    }
+}
+
+/// Check if the content provided is worth precompressing.
+fn is_compressible(content_type: &str, content: &[u8]) -> bool {
+    content.len() >= MIN_COMPRESSION_SIZE
+        && (content_type.starts_with("text/") || COMPRESSIBLE_CONTENT_TYPES.contains(&content_type))
+}
+
+/// Return the relative path provided as a string using `/` as separator.
+fn relative_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a new output writer for the directory provided.
+    fn new_output(dir: &Path, compression: &CompressionSettings) -> Output {
+        Output::new(dir, "digest".to_string(), compression).unwrap()
+    }
+
+    #[test]
+    fn files_not_written_again_are_removed_using_the_previous_manifest() {
+        let output_dir = tempfile::tempdir().unwrap();
+        let compression = CompressionSettings::default();
+
+        let mut output = new_output(output_dir.path(), &compression);
+        output.write("index.html", "index").unwrap();
+        output.write("data/user1.json", "{}").unwrap();
+        output.write("data/user2.json", "{}").unwrap();
+        assert_eq!(
+            output.finish().unwrap(),
+            OutputStats {
+                added: 3,
+                ..OutputStats::default()
+            }
+        );
+
+        // Files not written by this build are removed (along with the
+        // directories left empty), and unchanged ones are kept
+        fs::write(output_dir.path().join("custom.txt"), "custom").unwrap();
+        let mut output = new_output(output_dir.path(), &compression);
+        output.write("index.html", "index v2").unwrap();
+        output.write("data/user1.json", "{}").unwrap();
+        output.write("images/logo.svg", "svg").unwrap();
+        assert_eq!(
+            output.stale_files().unwrap(),
+            BTreeSet::from(["data/user2.json".to_string()])
+        );
+        assert_eq!(
+            output.finish().unwrap(),
+            OutputStats {
+                added: 1,
+                updated: 1,
+                removed: 1,
+                unchanged: 1,
+            }
+        );
+        assert!(!output_dir.path().join("data/user2.json").exists());
+        assert!(output_dir.path().join("custom.txt").exists());
+        assert_eq!(
+            fs::read_to_string(output_dir.path().join("index.html")).unwrap(),
+            "index v2"
+        );
+
+        let manifest = Manifest::read(output_dir.path()).unwrap().unwrap();
+        assert_eq!(
+            manifest.files.keys().collect::<Vec<_>>(),
+            vec!["data/user1.json", "images/logo.svg", "index.html"]
+        );
+    }
+
+    #[test]
+    fn files_in_written_dirs_are_removed_when_there_is_no_manifest() {
+        let output_dir = tempfile::tempdir().unwrap();
+        for (path, content) in [
+            ("index.html", "index"),
+            ("custom.txt", "custom"),
+            ("data/user1.json", "{}"),
+            ("data/a/user2.json", "{}"),
+            ("other/file.txt", "other"),
+        ] {
+            let path = output_dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        let mut output = new_output(output_dir.path(), &CompressionSettings::default());
+        output.write("index.html", "index").unwrap();
+        output.write("data/user1.json", "{}").unwrap();
+        assert_eq!(
+            output.stale_files().unwrap(),
+            BTreeSet::from(["data/a/user2.json".to_string()])
+        );
+        let stats = output.finish().unwrap();
+
+        assert_eq!(stats.removed, 1);
+        assert_eq!(stats.unchanged, 2);
+        assert!(!output_dir.path().join("data/a").exists());
+        assert!(output_dir.path().join("custom.txt").exists());
+        assert!(output_dir.path().join("other/file.txt").exists());
+    }
+
+    #[test]
+    fn precompressed_variants_are_written_and_kept_while_unchanged() {
+        let output_dir = tempfile::tempdir().unwrap();
+        let compression = CompressionSettings {
+            gzip: true,
+            brotli: true,
+        };
+        let content = "{}".repeat(MIN_COMPRESSION_SIZE);
+
+        let mut output = new_output(output_dir.path(), &compression);
+        output.write("data/user1.json", &content).unwrap();
+        output.write("data/small.json", "{}").unwrap();
+        output.finish().unwrap();
+        assert!(output_dir.path().join("data/user1.json.gz").exists());
+        assert!(output_dir.path().join("data/user1.json.br").exists());
+        assert!(!output_dir.path().join("data/small.json.gz").exists());
+
+        let mut output = new_output(output_dir.path(), &compression);
+        output.write("data/user1.json", &content).unwrap();
+        assert!(output.stale_files().unwrap().contains("data/small.json"));
+        let stats = output.finish().unwrap();
+        assert_eq!(
+            stats,
+            OutputStats {
+                removed: 1,
+                unchanged: 3,
+                ..OutputStats::default()
+            }
+        );
+        let manifest = Manifest::read(output_dir.path()).unwrap().unwrap();
+        assert_eq!(
+            manifest.files["data/user1.json.gz"].content_encoding.as_deref(),
+            Some("gzip")
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! This module is in charge of generating the search index data files.
+
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Serialize;
+use tracing::{debug, instrument};
+
+use crate::build::{DATA_PATH, db, output::Output, settings::SearchSettings};
+
+/// Path where the search index will be written to in the data directory.
+const INDEX_PATH: &str = "_index";
+
+/// Path where the n-grams shards will be written to in the index directory.
+const NGRAMS_PATH: &str = "_ngrams";
+
+/// Length of the logins prefixes used to shard the index.
+const PREFIX_LENGTH: usize = 3;
+
+/// Search index metadata, used by the web application to know how to query
+/// the index shards.
+#[derive(Debug, Clone, Serialize)]
+struct IndexMeta {
+    contributors: i64,
+    prefix_length: usize,
+    ngrams: bool,
+}
+
+/// Generate the search index data files.
+///
+/// Contributors (login -> id) are sharded by the lowercased prefix of their
+/// login, so that the web application only needs to fetch the shard matching
+/// what the user is typing. When enabled, contributors are also indexed by
+/// the trigrams found in their login to allow fuzzy matching.
+#[instrument(skip_all, err)]
+pub(crate) fn generate_search_index(
+    output: &mut Output,
+    contribs_db: &duckdb::Connection,
+    settings: &SearchSettings,
+) -> Result<()> {
+    debug!("generating search index data files");
+
+    // Write prefix shards
+    let index_path = Path::new(DATA_PATH).join(INDEX_PATH);
+    write_shards(
+        output,
+        contribs_db,
+        db::GET_SEARCH_INDEX_SHARDS,
+        Some(PREFIX_LENGTH),
+        &index_path,
+    )?;
+
+    // Write n-grams shards (if enabled)
+    if settings.ngrams {
+        let ngrams_path = index_path.join(NGRAMS_PATH);
+        write_shards(
+            output,
+            contribs_db,
+            db::GET_SEARCH_INDEX_NGRAMS_SHARDS,
+            None,
+            &ngrams_path,
+        )?;
+    }
+
+    // Write index metadata
+    let meta = IndexMeta {
+        contributors: contribs_db.query_row(db::GET_CONTRIBUTORS_COUNT, [], |row| row.get(0))?,
+        prefix_length: PREFIX_LENGTH,
+        ngrams: settings.ngrams,
+    };
+    output.write(index_path.join("_meta.json"), serde_json::to_vec(&meta)?)?;
+
+    Ok(())
+}
+
+/// Write the shards returned by the query provided to the path given.
+fn write_shards(
+    output: &mut Output,
+    contribs_db: &duckdb::Connection,
+    query: &str,
+    param: Option<usize>,
+    path: &Path,
+) -> Result<()> {
+    let mut stmt = contribs_db.prepare(query)?;
+    let map_row = |row: &duckdb::Row| {
+        let key: String = row.get(0)?;
+        let shard: String = row.get(1)?;
+        Ok((key, shard))
+    };
+    let rows = match param {
+        Some(param) => stmt.query_map([param], map_row)?.collect::<Result<Vec<_>, _>>()?,
+        None => stmt.query_map([], map_row)?.collect::<Result<Vec<_>, _>>()?,
+    };
+    for (key, shard) in rows {
+        output.write(path.join(format!("{key}.json")), shard)?;
+    }
+
+    Ok(())
+}
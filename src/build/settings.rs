@@ -2,7 +2,8 @@
 
 use std::{fs::File, path::Path};
 
-use anyhow::Result;
+use anyhow::{Context, Result, ensure};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 /// ContribCard settings.
@@ -13,6 +14,26 @@ pub(crate) struct Settings {
     #[serde(default)]
     pub repositories: Vec<String>,
     pub theme: Theme,
+
+    #[serde(default)]
+    pub compression: CompressionSettings,
+    #[serde(default)]
+    pub data_layout: DataLayoutSettings,
+    #[serde(default)]
+    pub feeds: FeedsSettings,
+    #[serde(default)]
+    pub leaderboards: LeaderboardsSettings,
+    #[serde(default)]
+    pub milestones: MilestonesSettings,
+    #[serde(default)]
+    pub projects: Vec<Project>,
+    #[serde(default)]
+    pub ranking: RankingSettings,
+    #[serde(default)]
+    pub search: SearchSettings,
+    /// Custom robots.txt content (a default one will be used if not provided).
+    pub robots_txt: Option<String>,
+    pub wrapped: Option<WrappedSettings>,
 }
 
 impl Settings {
@@ -20,20 +41,365 @@ impl Settings {
     pub(crate) fn new(path: &Path) -> Result<Self> {
         let file = File::open(path)?;
         let settings: Self = serde_yaml::from_reader(file)?;
+        settings.validate()?;
 
         Ok(settings)
     }
+
+    /// Validate the settings.
+    fn validate(&self) -> Result<()> {
+        // Projects names are used as file names (i.e. leaderboards and feeds)
+        for project in &self.projects {
+            ensure!(
+                is_valid_project_name(&project.name),
+                "invalid project name (only alphanumeric characters, '-', '_' and '.' are allowed, and it \
+                 cannot start with '.'): {}",
+                project.name
+            );
+        }
+
+        // Reviews are only supported by milestones
+        ensure!(
+            !self.leaderboards.kinds.contains(&ContributionKind::Review),
+            "invalid leaderboards contribution kind (review is only supported by milestones)"
+        );
+
+        // Bots pattern is used by the database, so it must be valid
+        if self.leaderboards.exclude_bots {
+            Regex::new(&self.leaderboards.bots_pattern).context("invalid leaderboards bots pattern")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Project settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Project {
+    /// Name of the project (only alphanumeric characters, '-', '_' and '.'
+    /// are allowed, as it's used in some file names).
+    pub name: String,
+    /// Repositories that belong to the project, in owner/repo format (use
+    /// owner/* to include all the repositories of an owner).
+    pub repositories: Vec<String>,
+}
+
+/// Ranking settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RankingSettings {
+    /// Rankings where the contributor isn't within this top percent won't be
+    /// included in their data file.
+    #[serde(default = "default_ranking_max_top_percent")]
+    pub max_top_percent: f64,
+    /// Minimum number of contributions required to include a ranking.
+    #[serde(default)]
+    pub min_contributions: u64,
+}
+
+impl Default for RankingSettings {
+    fn default() -> Self {
+        Self {
+            max_top_percent: default_ranking_max_top_percent(),
+            min_contributions: 0,
+        }
+    }
+}
+
+/// Search settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SearchSettings {
+    /// Whether contributors should also be indexed by the trigrams of their
+    /// login, to allow fuzzy matching in the web search.
+    #[serde(default)]
+    pub ngrams: bool,
 }
 
 /// Theme settings.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct Theme {
+    /// Badges settings (badges won't be generated when not provided).
+    pub badge: Option<BadgeTheme>,
     pub base_url: String,
     pub email_subject: String,
     pub favicon_url: String,
     pub logo_url: String,
     pub og_description: String,
     pub og_image_url: String,
+    /// Whether an Open Graph image should be rendered for each contributor.
+    #[serde(default)]
+    pub og_images: bool,
     pub og_title: String,
     pub social_message: String,
 }
+
+/// Badge theme settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BadgeTheme {
+    /// Label displayed in the contributors badges (i.e. CNCF contributor).
+    pub label: String,
+    #[serde(default = "default_badge_label_color")]
+    pub label_color: String,
+    #[serde(default = "default_badge_color")]
+    pub color: String,
+}
+
+/// Compression settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CompressionSettings {
+    /// Whether gzip precompressed variants (.gz) of the output files should
+    /// be generated.
+    #[serde(default)]
+    pub gzip: bool,
+    /// Whether brotli precompressed variants (.br) of the output files should
+    /// be generated.
+    #[serde(default)]
+    pub brotli: bool,
+}
+
+/// Data layout settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DataLayoutSettings {
+    /// Number of nested subdirectories (named after the first characters of
+    /// the login) used to shard the contributors data files (none by default,
+    /// i.e. flat layout).
+    #[serde(default)]
+    pub shard_depth: usize,
+}
+
+/// Feeds settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct FeedsSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Contributors whose first contribution happened within this number of
+    /// days will be included in the new contributors feeds.
+    #[serde(default = "default_feeds_days")]
+    pub days: u32,
+}
+
+impl Default for FeedsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            days: default_feeds_days(),
+        }
+    }
+}
+
+/// Leaderboards settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LeaderboardsSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Maximum number of contributors included in each leaderboard.
+    #[serde(default = "default_leaderboards_size")]
+    pub size: usize,
+    /// Kinds of contributions taken into account (all when empty).
+    #[serde(default)]
+    pub kinds: Vec<ContributionKind>,
+    #[serde(default = "default_true")]
+    pub exclude_bots: bool,
+    /// Regular expression used to detect bots by their login.
+    #[serde(default = "default_leaderboards_bots_pattern")]
+    pub bots_pattern: String,
+    /// Logins that won't be included in the leaderboards.
+    #[serde(default)]
+    pub excluded_logins: Vec<String>,
+}
+
+impl Default for LeaderboardsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            size: default_leaderboards_size(),
+            kinds: vec![],
+            exclude_bots: true,
+            bots_pattern: default_leaderboards_bots_pattern(),
+            excluded_logins: vec![],
+        }
+    }
+}
+
+/// Milestones settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MilestonesSettings {
+    #[serde(default = "default_true")]
+    pub include_defaults: bool,
+    #[serde(default)]
+    pub rules: Vec<MilestoneRule>,
+}
+
+impl MilestonesSettings {
+    /// Return the rules to apply (the default ones, unless disabled, followed
+    /// by the custom ones).
+    pub(crate) fn rules(&self) -> Vec<MilestoneRule> {
+        let mut rules = vec![];
+        if self.include_defaults {
+            rules.extend(default_milestones_rules());
+        }
+        rules.extend(self.rules.iter().cloned());
+        rules
+    }
+}
+
+impl Default for MilestonesSettings {
+    fn default() -> Self {
+        Self {
+            include_defaults: true,
+            rules: vec![],
+        }
+    }
+}
+
+/// Year in review (wrapped) settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct WrappedSettings {
+    pub year: i32,
+}
+
+/// Milestone rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MilestoneRule {
+    pub id: String,
+    pub title: String,
+    #[serde(flatten)]
+    pub condition: MilestoneCondition,
+}
+
+/// Condition that must be met to achieve a milestone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum MilestoneCondition {
+    /// Number of contributions (of a given kind, if provided).
+    Contributions {
+        kind: Option<ContributionKind>,
+        count: usize,
+    },
+    /// Number of distinct repositories contributed to.
+    Repositories { count: usize },
+    /// Number of years since the first contribution.
+    Tenure { years: u32 },
+}
+
+/// Kind of contribution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ContributionKind {
+    Commit,
+    Issue,
+    IssueCompleted,
+    PullRequest,
+    PullRequestMerged,
+    /// Pull request reviewed (only supported by milestones).
+    Review,
+}
+
+impl ContributionKind {
+    /// Return the name of the contribution kind.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            ContributionKind::Commit => "commit",
+            ContributionKind::Issue => "issue",
+            ContributionKind::IssueCompleted => "issue_completed",
+            ContributionKind::PullRequest => "pull_request",
+            ContributionKind::PullRequestMerged => "pull_request_merged",
+            ContributionKind::Review => "review",
+        }
+    }
+}
+
+/// Milestones rules included by default.
+fn default_milestones_rules() -> Vec<MilestoneRule> {
+    let rule = |id: &str, title: &str, condition| MilestoneRule {
+        id: id.to_string(),
+        title: title.to_string(),
+        condition,
+    };
+
+    vec![
+        rule(
+            "first-merged-pull-request",
+            "First merged pull request",
+            MilestoneCondition::Contributions {
+                kind: Some(ContributionKind::PullRequestMerged),
+                count: 1,
+            },
+        ),
+        rule(
+            "first-review",
+            "First review",
+            MilestoneCondition::Contributions {
+                kind: Some(ContributionKind::Review),
+                count: 1,
+            },
+        ),
+        rule(
+            "100-pull-requests",
+            "100th pull request",
+            MilestoneCondition::Contributions {
+                kind: Some(ContributionKind::PullRequest),
+                count: 100,
+            },
+        ),
+        rule(
+            "1000-contributions",
+            "1000th contribution",
+            MilestoneCondition::Contributions {
+                kind: None,
+                count: 1000,
+            },
+        ),
+        rule(
+            "10-repositories",
+            "Contributed to 10 repositories",
+            MilestoneCondition::Repositories { count: 10 },
+        ),
+        rule(
+            "5-years",
+            "5 years contributing",
+            MilestoneCondition::Tenure { years: 5 },
+        ),
+    ]
+}
+
+/// Default color of the badges message section.
+fn default_badge_color() -> String {
+    "#0086ff".to_string()
+}
+
+/// Default color of the badges label section.
+fn default_badge_label_color() -> String {
+    "#555555".to_string()
+}
+
+/// Default number of days taken into account in the new contributors feeds.
+fn default_feeds_days() -> u32 {
+    30
+}
+
+/// Default pattern used to detect bots in leaderboards.
+fn default_leaderboards_bots_pattern() -> String {
+    r"(\[bot\]|-bot|-robot)$".to_string()
+}
+
+/// Default number of contributors included in each leaderboard.
+fn default_leaderboards_size() -> usize {
+    100
+}
+
+/// Default maximum top percent used in rankings.
+fn default_ranking_max_top_percent() -> f64 {
+    25.0
+}
+
+/// Helper function used to set boolean fields that default to true.
+fn default_true() -> bool {
+    true
+}
+
+/// Check if the project name provided is valid.
+fn is_valid_project_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
@@ -0,0 +1,104 @@
+//! This module is in charge of generating the sitemap and robots.txt files.
+
+use std::{fmt::Write, path::Path};
+
+use anyhow::Result;
+use tracing::{debug, instrument};
+
+use crate::build::{contributor_url, db, output::Output, reserved_paths, settings::Settings};
+
+/// Maximum number of urls per sitemap file (as defined by the protocol).
+const MAX_URLS_PER_SITEMAP: usize = 50_000;
+
+/// Path where the sitemap chunks will be written to in the output directory
+/// (when the urls don't fit in a single sitemap).
+pub(crate) const SITEMAPS_PATH: &str = "sitemaps";
+
+/// File name of the sitemap (or sitemap index).
+const SITEMAP_FILE: &str = "sitemap.xml";
+
+/// Generate the sitemap listing the contributors pages, as well as the
+/// robots.txt file.
+#[instrument(skip_all, err)]
+pub(crate) fn generate_sitemap(
+    output: &mut Output,
+    contribs_db: &duckdb::Connection,
+    settings: &Settings,
+) -> Result<()> {
+    debug!("generating sitemap and robots.txt files");
+
+    let base_url = settings.theme.base_url.trim_end_matches('/');
+
+    // Prepare urls (home page and contributors pages)
+    let mut urls = vec![(format!("{base_url}/"), None)];
+    let mut stmt = contribs_db.prepare(db::GET_CONTRIBUTORS_LAST_CONTRIBUTION_DATE)?;
+    let rows = stmt.query_map([], |row| {
+        let login: String = row.get(0)?;
+        let last_contribution_date: String = row.get(1)?;
+        Ok((login, last_contribution_date))
+    })?;
+    let reserved_paths = reserved_paths();
+    for row in rows {
+        let (login, last_contribution_date) = row?;
+        if reserved_paths.contains(&login.to_lowercase()) {
+            continue;
+        }
+        urls.push((contributor_url(base_url, &login), Some(last_contribution_date)));
+    }
+
+    // Write sitemap (split in chunks referenced from a sitemap index when
+    // there are too many urls)
+    if urls.len() <= MAX_URLS_PER_SITEMAP {
+        output.write(SITEMAP_FILE, render_urlset(&urls)?)?;
+    } else {
+        let sitemaps_path = Path::new(SITEMAPS_PATH);
+        let mut index = String::new();
+        writeln!(index, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            index,
+            r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#
+        )?;
+        for (i, chunk) in urls.chunks(MAX_URLS_PER_SITEMAP).enumerate() {
+            let file_name = format!("sitemap-{}.xml", i + 1);
+            output.write(sitemaps_path.join(&file_name), render_urlset(chunk)?)?;
+            writeln!(
+                index,
+                "  <sitemap><loc>{base_url}/{SITEMAPS_PATH}/{file_name}</loc></sitemap>"
+            )?;
+        }
+        writeln!(index, "</sitemapindex>")?;
+        output.write(SITEMAP_FILE, index)?;
+    }
+
+    // Write robots.txt
+    let robots = match &settings.robots_txt {
+        Some(robots) => robots.clone(),
+        None => format!("User-agent: *\nAllow: /\n\nSitemap: {base_url}/{SITEMAP_FILE}\n"),
+    };
+    output.write("robots.txt", robots)?;
+
+    Ok(())
+}
+
+/// Render a sitemap urlset with the urls (and last modification dates)
+/// provided.
+fn render_urlset(urls: &[(String, Option<String>)]) -> Result<String> {
+    let mut urlset = String::new();
+    writeln!(urlset, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        urlset,
+        r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#
+    )?;
+    for (loc, lastmod) in urls {
+        match lastmod {
+            Some(lastmod) => writeln!(
+                urlset,
+                "  <url><loc>{loc}</loc><lastmod>{lastmod}</lastmod></url>"
+            )?,
+            None => writeln!(urlset, "  <url><loc>{loc}</loc></url>")?,
+        }
+    }
+    writeln!(urlset, "</urlset>")?;
+
+    Ok(urlset)
+}
@@ -0,0 +1,84 @@
+//! This module is in charge of generating the year in review (wrapped) data
+//! files and pages.
+
+use std::path::Path;
+
+use anyhow::Result;
+use askama::Template;
+use tracing::{debug, instrument};
+
+use crate::build::{
+    DATA_PATH, Features, Index, Meta, contributor_image_url, db,
+    output::Output,
+    settings::{Theme, WrappedSettings},
+};
+
+/// Path where the wrapped data files and pages will be written to.
+pub(crate) const WRAPPED_PATH: &str = "wrapped";
+
+/// Generate the wrapped data file and page of each of the contributors who
+/// contributed in the year configured.
+#[instrument(skip_all, err)]
+pub(crate) fn generate_wrapped(
+    output: &mut Output,
+    contribs_db: &duckdb::Connection,
+    settings: &WrappedSettings,
+    theme: &Theme,
+    features: Features,
+) -> Result<()> {
+    debug!(year = settings.year, "generating wrapped data files and pages");
+
+    // Get all wrapped summaries from database
+    let mut stmt = contribs_db.prepare(db::GET_WRAPPED_SUMMARIES)?;
+    let rows = stmt.query_map([settings.year], |row| {
+        let user: String = row.get(0)?;
+        let total: i64 = row.get(1)?;
+        let repositories: i64 = row.get(2)?;
+        let summary: String = row.get(3)?;
+        Ok((user, total, repositories, summary))
+    })?;
+
+    // Write the data file and the page of each of them
+    let year = settings.year;
+    let data_path = Path::new(DATA_PATH).join(WRAPPED_PATH).join(year.to_string());
+    let pages_path = Path::new(WRAPPED_PATH).join(year.to_string());
+    let default_meta = Meta::new(theme)?;
+    for row in rows {
+        let (user, total, repositories, summary) = row?;
+
+        // Data file
+        output.write(data_path.join(format!("{user}.json")), summary)?;
+
+        // Page
+        let meta = Meta {
+            title: format!("{user}'s {year} in review | {}", theme.og_title),
+            description: format!(
+                "{total} {} to {repositories} {} in {year}",
+                if total == 1 {
+                    "contribution"
+                } else {
+                    "contributions"
+                },
+                if repositories == 1 {
+                    "repository"
+                } else {
+                    "repositories"
+                },
+            ),
+            url: format!(
+                "{}/{WRAPPED_PATH}/{year}/{user}/",
+                theme.base_url.trim_end_matches('/')
+            ),
+            image_url: contributor_image_url(theme, &user, &default_meta.image_url),
+        };
+        let page = Index {
+            features,
+            meta,
+            theme,
+        }
+        .render()?;
+        output.write(pages_path.join(&user).join("index.html"), page)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,334 @@
+//! This module defines the functionality of the deploy CLI subcommand for the
+//! local directory provider.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{Context, Result, bail};
+#[cfg(unix)]
+use chrono::Utc;
+use tracing::{debug, info, instrument};
+
+use crate::{
+    LocalArgs,
+    deploy::plan::{Key, Plan, get_dir_objects},
+};
+
+/// Format of the ids of the versions of the website (sorting them
+/// lexicographically sorts them chronologically as well).
+#[cfg(unix)]
+const VERSION_ID_FORMAT: &str = "%Y%m%d%H%M%S%f";
+
+/// Deploy contribcard website to a local directory.
+///
+/// The new version of the website is prepared in a staging directory next to
+/// the target one, which is swapped with it once ready. Files that haven't
+/// changed are hard linked from the target directory instead of being copied
+/// again, and stale files are not carried over (unless they are protected).
+///
+/// On Unix systems the target directory is a symbolic link to the current
+/// version of the website, so that the swap is atomic (the new version is
+/// prepared in a versions directory next to the target one, and a symbolic
+/// link to it is renamed over the target).
+#[instrument(skip_all, err)]
+pub(crate) fn deploy(args: &LocalArgs) -> Result<()> {
+    info!("deploying contribcard website..");
+    let start = Instant::now();
+
+    // Get deployed files and prepare deploy plan
+    let deployed_objects = get_dir_objects(&args.target_dir)?;
+    let plan = Plan::new(
+        &args.content_dir,
+        &deployed_objects,
+        &args.protected_prefixes,
+        false,
+    )?;
+    info!(
+        new = plan.new.len(),
+        changed = plan.changed.len(),
+        unchanged = plan.unchanged.len(),
+        stale = plan.stale.len(),
+        "deploy plan ready"
+    );
+
+    // Print plan and stop here when running in dry-run mode
+    if args.dry_run {
+        plan.print(args.plan_format)?;
+        return Ok(());
+    }
+
+    // Prepare staging directory
+    let staging_dir = staging_dir(&args.target_dir)?;
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir).context("error removing previous staging directory")?;
+    }
+    fs::create_dir_all(&staging_dir)?;
+
+    // Copy new and changed files to the staging directory
+    for object in plan.new.iter().chain(plan.changed.iter()) {
+        let dst = staging_file(&staging_dir, &object.key)?;
+        fs::copy(&object.path, &dst).context(format!("error copying file {}", object.key))?;
+        debug!(key = object.key, "file copied");
+    }
+
+    // Link the remaining deployed files (unchanged and protected ones) from
+    // the target directory
+    let copied_keys: HashSet<&Key> =
+        plan.new.iter().chain(plan.changed.iter()).map(|object| &object.key).collect();
+    let stale_keys: HashSet<&Key> = plan.stale.iter().collect();
+    for key in deployed_objects.keys() {
+        if copied_keys.contains(key) || stale_keys.contains(key) {
+            continue;
+        }
+        let src = args.target_dir.join(key);
+        let dst = staging_file(&staging_dir, key)?;
+        if fs::hard_link(&src, &dst).is_err() {
+            fs::copy(&src, &dst).context(format!("error copying file {key}"))?;
+        }
+    }
+
+    // Swap the staging and target directories
+    swap_dirs(&staging_dir, &args.target_dir)?;
+
+    let duration = start.elapsed().as_secs_f64();
+    info!("contribcard website deployed! (took: {:.3}s)", duration);
+
+    Ok(())
+}
+
+/// Return the path of the staging directory where the new version of the
+/// website will be prepared. On Unix systems it's a new version in the
+/// versions directory next to the target one.
+#[cfg(unix)]
+fn staging_dir(target_dir: &Path) -> Result<PathBuf> {
+    let version = Utc::now().format(VERSION_ID_FORMAT).to_string();
+    Ok(sibling_dir(target_dir, "versions")?.join(version))
+}
+
+/// Return the path of the staging directory where the new version of the
+/// website will be prepared.
+#[cfg(not(unix))]
+fn staging_dir(target_dir: &Path) -> Result<PathBuf> {
+    sibling_dir(target_dir, "staging")
+}
+
+/// Swap the staging directory (a version in the versions directory) with
+/// the target one atomically.
+///
+/// The target directory is a symbolic link to the current version, so a new
+/// symbolic link pointing to the staging directory is renamed over it. When
+/// the target is a regular directory (i.e. deployed by a previous version),
+/// it's moved to the versions directory first. Once the swap is done, the
+/// previous versions are removed.
+#[cfg(unix)]
+#[instrument(err)]
+fn swap_dirs(staging_dir: &Path, target_dir: &Path) -> Result<()> {
+    let versions_dir = sibling_dir(target_dir, "versions")?;
+
+    // Move the target directory to the versions directory if it isn't a
+    // symbolic link yet (this is only needed once, so it isn't atomic)
+    if fs::symlink_metadata(target_dir).is_ok_and(|metadata| !metadata.file_type().is_symlink()) {
+        fs::rename(target_dir, versions_dir.join("previous")).context("error moving target directory")?;
+    }
+
+    // Point the target to the staging directory (the link is relative to the
+    // parent directory, so that both can be moved together)
+    let Some(version) = staging_dir.file_name() else {
+        bail!("invalid staging directory: {}", staging_dir.display());
+    };
+    let Some(versions_dir_name) = versions_dir.file_name() else {
+        bail!("invalid versions directory: {}", versions_dir.display());
+    };
+    let link = sibling_dir(target_dir, "link")?;
+    if fs::symlink_metadata(&link).is_ok() {
+        fs::remove_file(&link)?;
+    }
+    std::os::unix::fs::symlink(Path::new(versions_dir_name).join(version), &link)
+        .context("error creating target link")?;
+    fs::rename(&link, target_dir).context("error swapping target link")?;
+
+    // Remove previous versions
+    for dir_entry in fs::read_dir(&versions_dir)? {
+        let dir_entry = dir_entry?;
+        if dir_entry.file_name() != version {
+            fs::remove_dir_all(dir_entry.path()).context("error removing previous version")?;
+        }
+    }
+
+    debug!("staging directory swapped");
+    Ok(())
+}
+
+/// Swap the staging directory with the target one. The previous version of
+/// the target directory is removed once the swap is done.
+#[cfg(not(unix))]
+#[instrument(err)]
+fn swap_dirs(staging_dir: &Path, target_dir: &Path) -> Result<()> {
+    if target_dir.exists() {
+        let previous_dir = sibling_dir(target_dir, "previous")?;
+        if previous_dir.exists() {
+            fs::remove_dir_all(&previous_dir)?;
+        }
+        fs::rename(target_dir, &previous_dir).context("error moving target directory")?;
+        fs::rename(staging_dir, target_dir).context("error moving staging directory")?;
+        fs::remove_dir_all(&previous_dir).context("error removing previous directory")?;
+    } else {
+        fs::rename(staging_dir, target_dir).context("error moving staging directory")?;
+    }
+
+    debug!("staging directory swapped");
+    Ok(())
+}
+
+/// Return the path of the directory next to the target one with the suffix
+/// provided (i.e. `.site.staging` for a `site` target directory). Keeping it
+/// in the same parent directory ensures both are on the same filesystem, so
+/// that they can be renamed.
+fn sibling_dir(target_dir: &Path, suffix: &str) -> Result<PathBuf> {
+    let Some(name) = target_dir.file_name() else {
+        bail!("invalid target directory: {}", target_dir.display());
+    };
+    let parent = match target_dir.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    if !parent.exists() {
+        fs::create_dir_all(parent)?;
+    }
+
+    Ok(parent.join(format!(".{}.{suffix}", name.to_string_lossy())))
+}
+
+/// Return the path of the file with the key provided in the staging
+/// directory, creating its parent directories if needed.
+fn staging_file(staging_dir: &Path, key: &str) -> Result<PathBuf> {
+    let path = staging_dir.join(key);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlanFormat;
+
+    /// Write the files provided to the directory given.
+    fn write_files(dir: &Path, files: &[(&str, &str)]) {
+        for (key, content) in files {
+            let path = dir.join(key);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+    }
+
+    /// Return the names of the entries of the directory provided.
+    fn dir_entries(dir: &Path) -> Vec<String> {
+        let mut entries: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|dir_entry| dir_entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn swap_dirs_migrates_plain_target_dir_to_symlink() {
+        let parent_dir = tempfile::tempdir().unwrap();
+        let target_dir = parent_dir.path().join("site");
+        write_files(&target_dir, &[("index.html", "v1")]);
+
+        // First swap: the plain target directory is replaced by a link
+        let first_staging_dir = staging_dir(&target_dir).unwrap();
+        write_files(&first_staging_dir, &[("index.html", "v2")]);
+        swap_dirs(&first_staging_dir, &target_dir).unwrap();
+        assert!(fs::symlink_metadata(&target_dir).unwrap().file_type().is_symlink());
+        assert_eq!(
+            fs::read_link(&target_dir).unwrap(),
+            Path::new(".site.versions").join(first_staging_dir.file_name().unwrap())
+        );
+        assert_eq!(fs::read_to_string(target_dir.join("index.html")).unwrap(), "v2");
+        assert_eq!(dir_entries(parent_dir.path()), vec![".site.versions", "site"]);
+
+        // Second swap: the link is replaced and the previous version removed
+        let next_staging_dir = staging_dir(&target_dir).unwrap();
+        write_files(&next_staging_dir, &[("index.html", "v3")]);
+        swap_dirs(&next_staging_dir, &target_dir).unwrap();
+        assert_eq!(fs::read_to_string(target_dir.join("index.html")).unwrap(), "v3");
+        assert_eq!(
+            dir_entries(&parent_dir.path().join(".site.versions")),
+            vec![next_staging_dir.file_name().unwrap().to_string_lossy().to_string()]
+        );
+        assert_eq!(dir_entries(parent_dir.path()), vec![".site.versions", "site"]);
+    }
+
+    #[test]
+    fn deploy_to_local_directory() {
+        let parent_dir = tempfile::tempdir().unwrap();
+        let content_dir = tempfile::tempdir().unwrap();
+        write_files(
+            content_dir.path(),
+            &[
+                ("index.html", "v1"),
+                ("data/user1.json", "{}"),
+                (".hidden.txt", "hidden"),
+            ],
+        );
+        let target_dir = parent_dir.path().join("site");
+        write_files(
+            &target_dir,
+            &[("stale.txt", "stale"), ("protected/file.txt", "protected")],
+        );
+        let args = LocalArgs {
+            content_dir: content_dir.path().to_path_buf(),
+            target_dir: target_dir.clone(),
+            dry_run: false,
+            plan_format: PlanFormat::Table,
+            protected_prefixes: vec!["protected/".to_string()],
+        };
+
+        deploy(&args).unwrap();
+        assert_eq!(dir_entries(&target_dir), vec!["data", "index.html", "protected"]);
+        assert_eq!(
+            fs::read_to_string(target_dir.join("protected/file.txt")).unwrap(),
+            "protected"
+        );
+
+        // Deploy changes (unchanged and protected files are carried over)
+        write_files(content_dir.path(), &[("index.html", "v2")]);
+        fs::remove_file(content_dir.path().join("data/user1.json")).unwrap();
+        deploy(&args).unwrap();
+        assert_eq!(dir_entries(&target_dir), vec!["index.html", "protected"]);
+        assert_eq!(fs::read_to_string(target_dir.join("index.html")).unwrap(), "v2");
+        assert_eq!(
+            fs::read_to_string(target_dir.join("protected/file.txt")).unwrap(),
+            "protected"
+        );
+    }
+
+    #[test]
+    fn dry_run_does_not_change_target_dir() {
+        let parent_dir = tempfile::tempdir().unwrap();
+        let content_dir = tempfile::tempdir().unwrap();
+        write_files(content_dir.path(), &[("index.html", "v1")]);
+        let target_dir = parent_dir.path().join("site");
+        let args = LocalArgs {
+            content_dir: content_dir.path().to_path_buf(),
+            target_dir: target_dir.clone(),
+            dry_run: true,
+            plan_format: PlanFormat::Json,
+            protected_prefixes: vec![],
+        };
+
+        deploy(&args).unwrap();
+        assert!(!target_dir.exists());
+        assert!(dir_entries(parent_dir.path()).is_empty());
+    }
+}
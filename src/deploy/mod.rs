@@ -1,3 +1,7 @@
 //! This module defines the functionality of the deploy CLI subcommand.
 
+pub(crate) mod git;
+pub(crate) mod local;
+pub(crate) mod plan;
+pub(crate) mod rules;
 pub(crate) mod s3;
@@ -0,0 +1,416 @@
+//! This module defines the deploy plan, which describes the changes required
+//! to make the deployed website match the content directory.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, bail, format_err};
+use serde::Serialize;
+use tracing::{debug, instrument};
+use walkdir::WalkDir;
+
+use crate::{
+    PlanFormat,
+    build::manifest::{Manifest, md5sum_file},
+};
+
+/// File name of the index document.
+pub(crate) const INDEX_DOCUMENT: &str = "index.html";
+
+/// Name of the git metadata directory (never deployed).
+const GIT_DIR: &str = ".git";
+
+/// Extension of the gzip precompressed variants.
+const GZIP_EXTENSION: &str = "gz";
+
+/// Extension of the brotli precompressed variants.
+const BROTLI_EXTENSION: &str = "br";
+
+/// Type alias to represent an object's checksum.
+pub(crate) type Checksum = String;
+
+/// Type alias to represent an object's key.
+pub(crate) type Key = String;
+
+/// Deploy plan.
+#[derive(Debug, Clone, Default, Serialize)]
+pub(crate) struct Plan {
+    /// Objects that are not deployed yet.
+    pub new: Vec<LocalObject>,
+    /// Objects deployed whose content has changed.
+    pub changed: Vec<LocalObject>,
+    /// Objects deployed whose content hasn't changed.
+    pub unchanged: Vec<Key>,
+    /// Objects deployed that are not present in the content directory
+    /// anymore (excluding the protected ones).
+    pub stale: Vec<Key>,
+}
+
+/// Object available in the content directory.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct LocalObject {
+    pub key: Key,
+    #[serde(skip)]
+    pub path: PathBuf,
+    pub checksum: Checksum,
+    pub size: u64,
+    pub content_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_encoding: Option<String>,
+}
+
+impl Plan {
+    /// Create a new deploy plan comparing the files in the content directory
+    /// with the deployed objects provided. Checksums recorded in the build
+    /// manifest are used when available.
+    ///
+    /// When inlining precompressed variants, the `.gz` and `.br` variants are
+    /// not deployed as separate objects. The gzip variant of a file (if any)
+    /// is deployed under the original key instead, using the corresponding
+    /// content encoding. This is useful for providers that cannot negotiate
+    /// the encoding with the clients (i.e. S3).
+    #[instrument(skip_all, err)]
+    pub(crate) fn new(
+        content_dir: &Path,
+        deployed_objects: &HashMap<Key, Option<Checksum>>,
+        protected_prefixes: &[String],
+        inline_precompressed: bool,
+    ) -> Result<Self> {
+        debug!("preparing deploy plan");
+
+        // Get regular files available in the content directory
+        let mut files = vec![];
+        for dir_entry in WalkDir::new(content_dir).sort_by_file_name() {
+            let dir_entry = dir_entry?;
+            if dir_entry.file_type().is_file() {
+                files.push((object_key(content_dir, dir_entry.path()), dir_entry));
+            }
+        }
+        let files_keys: HashSet<&str> = files.iter().map(|(key, _)| key.as_str()).collect();
+
+        let manifest = Manifest::read(content_dir)?.unwrap_or_default();
+        let mut plan = Plan::default();
+        let mut local_keys = HashSet::new();
+        for (key, dir_entry) in &files {
+            // Skip precompressed variants when they are inlined
+            if inline_precompressed && is_precompressed_variant(key, &files_keys) {
+                continue;
+            }
+            local_keys.insert(key.clone());
+
+            // Skip files that start with a dot
+            if key.starts_with('.') {
+                continue;
+            }
+
+            // Select the file to deploy (the gzip variant of the file when
+            // precompressed variants are inlined and it's available)
+            let gzip_key = format!("{key}.{GZIP_EXTENSION}");
+            let (file_key, path) = if inline_precompressed && files_keys.contains(gzip_key.as_str()) {
+                let path = content_dir.join(&gzip_key);
+                (gzip_key, path)
+            } else {
+                (key.clone(), dir_entry.path().to_path_buf())
+            };
+
+            // Prepare local object
+            let object = if let Some(entry) = manifest.files.get(&file_key) {
+                LocalObject {
+                    key: key.clone(),
+                    path,
+                    checksum: entry.md5.clone(),
+                    size: entry.size,
+                    content_type: entry.content_type.clone(),
+                    content_encoding: entry.content_encoding.clone(),
+                }
+            } else {
+                let (content_type, content_encoding) = guess_content_type_and_encoding(&file_key)?;
+                LocalObject {
+                    key: key.clone(),
+                    checksum: md5sum_file(&path)?,
+                    size: fs::metadata(&path)?.len(),
+                    path,
+                    content_type,
+                    content_encoding,
+                }
+            };
+
+            // Compare it with the deployed one
+            match deployed_objects.get(&object.key) {
+                None => plan.new.push(object),
+                Some(Some(remote_checksum)) if *remote_checksum == object.checksum => {
+                    plan.unchanged.push(object.key);
+                }
+                Some(_) => plan.changed.push(object),
+            }
+        }
+
+        // Select deployed objects not present locally
+        plan.stale = deployed_objects
+            .keys()
+            .filter(|key| !local_keys.contains(*key))
+            .filter(|key| !protected_prefixes.iter().any(|prefix| key.starts_with(prefix)))
+            .cloned()
+            .collect();
+        plan.stale.sort();
+
+        Ok(plan)
+    }
+
+    /// Return the objects that need to be uploaded (new and changed ones),
+    /// leaving the index document out, as it should be uploaded once all the
+    /// other objects have been uploaded successfully.
+    pub(crate) fn objects_to_upload(&self) -> impl Iterator<Item = &LocalObject> {
+        self.new.iter().chain(self.changed.iter()).filter(|object| object.key != INDEX_DOCUMENT)
+    }
+
+    /// Return the index document if it needs to be uploaded.
+    pub(crate) fn index_document_to_upload(&self) -> Option<&LocalObject> {
+        self.new.iter().chain(self.changed.iter()).find(|object| object.key == INDEX_DOCUMENT)
+    }
+
+    /// Check that the number of stale objects doesn't exceed the maximum
+    /// percentage of the deployed objects allowed to be deleted.
+    pub(crate) fn check_delete_threshold(
+        &self,
+        deployed_objects: usize,
+        max_delete_percent: f64,
+    ) -> Result<()> {
+        check_delete_threshold(self.stale.len(), deployed_objects, max_delete_percent)
+    }
+
+    /// Print the plan to stdout in the format provided.
+    pub(crate) fn print(&self, format: PlanFormat) -> Result<()> {
+        match format {
+            PlanFormat::Json => println!("{}", serde_json::to_string_pretty(self)?),
+            PlanFormat::Table => {
+                println!(
+                    "{} new, {} changed, {} unchanged, {} stale\n",
+                    self.new.len(),
+                    self.changed.len(),
+                    self.unchanged.len(),
+                    self.stale.len()
+                );
+                println!("{:<10}KEY", "ACTION");
+                for object in &self.new {
+                    println!("{:<10}{}", "new", object.key);
+                }
+                for object in &self.changed {
+                    println!("{:<10}{}", "changed", object.key);
+                }
+                for key in &self.stale {
+                    println!("{:<10}{key}", "stale");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Check that the number of stale objects provided doesn't exceed the maximum
+/// percentage of the deployed objects allowed to be deleted.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn check_delete_threshold(
+    stale_objects: usize,
+    deployed_objects: usize,
+    max_delete_percent: f64,
+) -> Result<()> {
+    if stale_objects == 0 {
+        return Ok(());
+    }
+    let delete_percent = stale_objects as f64 / deployed_objects as f64 * 100.0;
+    if delete_percent > max_delete_percent {
+        bail!(
+            "refusing to delete {stale_objects} of {deployed_objects} objects ({delete_percent:.1}%), \
+             which exceeds the maximum allowed ({max_delete_percent}%)"
+        );
+    }
+
+    Ok(())
+}
+
+/// Get the files in the directory provided (excluding the git metadata
+/// directory), returning their key and checksum. This is used by the
+/// providers deploying the website to a directory.
+#[instrument(err)]
+pub(crate) fn get_dir_objects(dir: &Path) -> Result<HashMap<Key, Option<Checksum>>> {
+    let mut objects = HashMap::new();
+    if !dir.exists() {
+        return Ok(objects);
+    }
+
+    let walker = WalkDir::new(dir).into_iter().filter_entry(|dir_entry| dir_entry.file_name() != GIT_DIR);
+    for dir_entry in walker {
+        let dir_entry = dir_entry?;
+        if !dir_entry.file_type().is_file() {
+            continue;
+        }
+        let key = object_key(dir, dir_entry.path());
+        objects.insert(key, Some(md5sum_file(dir_entry.path())?));
+    }
+
+    Ok(objects)
+}
+
+/// Return the key of the object corresponding to the file provided.
+fn object_key(content_dir: &Path, file: &Path) -> Key {
+    file.display()
+        .to_string()
+        .trim_start_matches(content_dir.display().to_string().as_str())
+        .trim_start_matches('/')
+        .to_string()
+}
+
+/// Check if the key provided belongs to a precompressed variant of any of
+/// the files given.
+fn is_precompressed_variant(key: &str, files_keys: &HashSet<&str>) -> bool {
+    [GZIP_EXTENSION, BROTLI_EXTENSION].iter().any(|extension| {
+        key.strip_suffix(extension)
+            .and_then(|key| key.strip_suffix('.'))
+            .is_some_and(|original_key| files_keys.contains(original_key))
+    })
+}
+
+/// Guess the content type and encoding of the object provided from its key.
+/// Precompressed variants (.gz and .br) use the content type of the original
+/// file along with the corresponding encoding.
+fn guess_content_type_and_encoding(key: &str) -> Result<(String, Option<String>)> {
+    let (original_key, content_encoding) = if let Some(original_key) = key.strip_suffix(".gz") {
+        (original_key, Some("gzip".to_string()))
+    } else if let Some(original_key) = key.strip_suffix(".br") {
+        (original_key, Some("br".to_string()))
+    } else {
+        (key, None)
+    };
+    let content_type = mime_guess::from_path(original_key)
+        .first()
+        .ok_or(format_err!("cannot detect content type of key: {})", &key))?;
+
+    Ok((content_type.essence_str().to_string(), content_encoding))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Setup a content directory with the files provided.
+    fn setup_content_dir(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let content_dir = tempfile::tempdir().unwrap();
+        for (key, content) in files {
+            let path = content_dir.path().join(key);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        content_dir
+    }
+
+    /// Return the keys of the objects provided.
+    fn keys(objects: &[LocalObject]) -> Vec<&str> {
+        objects.iter().map(|object| object.key.as_str()).collect()
+    }
+
+    #[test]
+    fn plan_classifies_objects() {
+        let content_dir = setup_content_dir(&[
+            ("index.html", "index"),
+            ("data/user1.json", "{}"),
+            ("data/user2.json", "{\"v\": 2}"),
+            (".hidden.txt", "hidden"),
+        ]);
+        let unchanged_checksum = md5sum_file(&content_dir.path().join("data/user1.json")).unwrap();
+        let deployed_objects = HashMap::from([
+            ("data/user1.json".to_string(), Some(unchanged_checksum)),
+            ("data/user2.json".to_string(), Some("outdated".to_string())),
+            ("data/user3.json".to_string(), Some("stale".to_string())),
+            ("old.html".to_string(), None),
+            ("images/logo.svg".to_string(), None),
+            ("images/og/user1.png".to_string(), None),
+        ]);
+        let protected_prefixes = vec!["images/".to_string()];
+
+        let plan = Plan::new(content_dir.path(), &deployed_objects, &protected_prefixes, false).unwrap();
+
+        assert_eq!(keys(&plan.new), vec!["index.html"]);
+        assert_eq!(keys(&plan.changed), vec!["data/user2.json"]);
+        assert_eq!(plan.unchanged, vec!["data/user1.json"]);
+        assert_eq!(plan.stale, vec!["data/user3.json", "old.html"]);
+        assert_eq!(plan.new[0].content_type, "text/html");
+        assert_eq!(plan.new[0].size, 5);
+        assert!(plan.objects_to_upload().all(|object| object.key != INDEX_DOCUMENT));
+        assert_eq!(plan.index_document_to_upload().unwrap().key, INDEX_DOCUMENT);
+    }
+
+    #[test]
+    fn plan_keeps_precompressed_variants_as_separate_objects() {
+        let content_dir =
+            setup_content_dir(&[("app.js", "js"), ("app.js.gz", "gzip"), ("app.js.br", "brotli")]);
+
+        let plan = Plan::new(content_dir.path(), &HashMap::new(), &[], false).unwrap();
+
+        assert_eq!(keys(&plan.new), vec!["app.js", "app.js.br", "app.js.gz"]);
+        assert_eq!(plan.new[0].content_encoding, None);
+        assert_eq!(plan.new[1].content_type, "text/javascript");
+        assert_eq!(plan.new[1].content_encoding.as_deref(), Some("br"));
+        assert_eq!(plan.new[2].content_encoding.as_deref(), Some("gzip"));
+    }
+
+    #[test]
+    fn plan_inlines_gzip_variants_under_the_original_keys() {
+        let content_dir = setup_content_dir(&[
+            ("app.js", "js"),
+            ("app.js.gz", "gzip"),
+            ("app.js.br", "brotli"),
+            ("logo.svg", "svg"),
+            ("notes.txt.gz", "notes"),
+        ]);
+        let deployed_objects = HashMap::from([("app.js.gz".to_string(), None)]);
+
+        let plan = Plan::new(content_dir.path(), &deployed_objects, &[], true).unwrap();
+
+        assert_eq!(keys(&plan.new), vec!["app.js", "logo.svg", "notes.txt.gz"]);
+        let app = &plan.new[0];
+        assert_eq!(app.path, content_dir.path().join("app.js.gz"));
+        assert_eq!(
+            app.checksum,
+            md5sum_file(&content_dir.path().join("app.js.gz")).unwrap()
+        );
+        assert_eq!(app.content_type, "text/javascript");
+        assert_eq!(app.content_encoding.as_deref(), Some("gzip"));
+        assert_eq!(plan.new[1].content_encoding, None);
+        assert_eq!(plan.new[2].content_encoding.as_deref(), Some("gzip"));
+        assert_eq!(plan.stale, vec!["app.js.gz"]);
+    }
+
+    #[test]
+    fn precompressed_variants_are_detected() {
+        let files_keys = HashSet::from(["app.js", "app.js.gz", "app.js.br", "archive.gz"]);
+
+        assert!(is_precompressed_variant("app.js.gz", &files_keys));
+        assert!(is_precompressed_variant("app.js.br", &files_keys));
+        assert!(!is_precompressed_variant("app.js", &files_keys));
+        assert!(!is_precompressed_variant("archive.gz", &files_keys));
+        assert!(!is_precompressed_variant("app.jsgz", &files_keys));
+        assert!(!is_precompressed_variant("other.js.gz", &files_keys));
+    }
+
+    #[test]
+    fn delete_threshold_is_enforced() {
+        assert!(check_delete_threshold(0, 0, 0.0).is_ok());
+        assert!(check_delete_threshold(0, 10, 0.0).is_ok());
+        assert!(check_delete_threshold(1, 4, 25.0).is_ok());
+        assert!(check_delete_threshold(2, 4, 25.0).is_err());
+        assert!(check_delete_threshold(1, 1, 100.0).is_ok());
+        assert!(check_delete_threshold(1, 10, 0.0).is_err());
+
+        let plan = Plan {
+            stale: vec!["a.html".to_string(), "b.html".to_string()],
+            ..Plan::default()
+        };
+        assert!(plan.check_delete_threshold(10, 20.0).is_ok());
+        assert!(plan.check_delete_threshold(4, 20.0).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+//! This module defines the rules used to set the cache control and metadata
+//! of the deployed objects.
+
+use std::{collections::HashMap, fs::File, path::Path};
+
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+
+/// Rules applied when no rules file is provided.
+const DEFAULT_RULES: &str = r#"
+rules:
+  - pattern: "assets/**"
+    cache_control: "public, max-age=31536000, immutable"
+  - pattern: "**/index.html"
+    cache_control: "public, max-age=0, must-revalidate"
+  - pattern: "data/**"
+    cache_control: "public, max-age=300"
+"#;
+
+/// Rules file.
+#[derive(Debug, Clone, Deserialize)]
+struct RulesFile {
+    #[serde(default)]
+    rules: Vec<ObjectRule>,
+}
+
+/// Rule applied to the objects whose key matches its pattern.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub(crate) struct ObjectRule {
+    /// Glob pattern matched against the object's key.
+    pub pattern: String,
+    pub cache_control: Option<String>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// Rules applied to the deployed objects.
+#[derive(Debug, Clone)]
+pub(crate) struct ObjectRules {
+    glob_set: GlobSet,
+    rules: Vec<ObjectRule>,
+}
+
+impl ObjectRules {
+    /// Create a new object rules instance from the rules file provided (the
+    /// default rules will be used when no file is provided).
+    pub(crate) fn new(rules_file: Option<&Path>) -> Result<Self> {
+        // Load rules
+        let RulesFile { rules } = match rules_file {
+            Some(path) => {
+                let file = File::open(path).context("error opening rules file")?;
+                serde_yaml::from_reader(file).context("error parsing rules file")?
+            }
+            None => serde_yaml::from_str(DEFAULT_RULES)?,
+        };
+
+        // Prepare the glob set used to match the rules patterns
+        let mut builder = GlobSetBuilder::new();
+        for rule in &rules {
+            let glob = GlobBuilder::new(&rule.pattern)
+                .literal_separator(true)
+                .build()
+                .context(format!("invalid rule pattern: {}", rule.pattern))?;
+            builder.add(glob);
+        }
+
+        Ok(Self {
+            glob_set: builder.build()?,
+            rules,
+        })
+    }
+
+    /// Return the first rule matching the key provided, if any.
+    pub(crate) fn get(&self, key: &str) -> Option<&ObjectRule> {
+        self.glob_set.matches(key).first().map(|index| &self.rules[*index])
+    }
+}
@@ -1,36 +1,68 @@
 //! This module defines the functionality of the deploy CLI subcommand for the
 //! AWS S3 provider.
 
-use std::{
-    collections::HashMap,
-    env,
-    fmt::Write,
-    fs,
-    path::{Path, PathBuf},
-    time::Instant,
-};
+use std::{collections::HashMap, env, fmt::Write, path::Path, time::Instant};
 
 use anyhow::{Context, Result, bail, format_err};
-use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::{
+    operation::get_object::GetObjectError,
+    primitives::{ByteStream, Length},
+    types::{CompletedMultipartUpload, CompletedPart, Delete, ObjectIdentifier},
+};
+use chrono::{DateTime, Utc};
 use futures::stream::{self, StreamExt};
-use md5::{Digest, Md5};
-use mime_guess::mime;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
 use tracing::{debug, info, instrument};
-use walkdir::WalkDir;
 
-use crate::S3Args;
-
-/// File name of the index document.
-const INDEX_DOCUMENT: &str = "index.html";
+use crate::{
+    RollbackArgs, S3Args, S3ConnectionArgs,
+    build::{DATA_PATH, manifest::Manifest},
+    deploy::{
+        plan::{Checksum, INDEX_DOCUMENT, Key, LocalObject, Plan, check_delete_threshold},
+        rules::ObjectRules,
+    },
+};
 
 /// Number of files to upload concurrently.
 const UPLOAD_FILES_CONCURRENCY: usize = 50;
 
-/// Type alias to represent an object's checksum.
-type Checksum = String;
+/// Maximum number of objects that can be deleted in a single request.
+const DELETE_OBJECTS_BATCH_SIZE: usize = 1000;
+
+/// Number of objects metadata to fetch concurrently.
+const HEAD_OBJECTS_CONCURRENCY: usize = 50;
+
+/// Number of objects to copy concurrently.
+const COPY_OBJECTS_CONCURRENCY: usize = 50;
+
+/// Files larger than this size will be uploaded using multipart uploads.
+const MULTIPART_UPLOAD_THRESHOLD: u64 = 16 * 1024 * 1024;
 
-/// Type alias to represent an object's key.
-type Key = String;
+/// Size of each of the parts of multipart uploads.
+const MULTIPART_UPLOAD_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Metadata key used to store the MD5 digest of the objects' content. This
+/// is used for change detection when the ETag is not an MD5 digest (i.e.
+/// objects uploaded using multipart uploads).
+const MD5_METADATA_KEY: &str = "content-md5";
+
+/// Key of the object pointing to the current release in versioned deploys
+/// (relative to the prefix). It contains the id of the current release, and
+/// the web application uses it to load the data files from that release.
+const CURRENT_RELEASE_KEY: &str = "current";
+
+/// Characters percent-encoded in the copy source of the objects copied (all
+/// but the unreserved ones and the path separator).
+const COPY_SOURCE_ENCODE_SET: &AsciiSet =
+    &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~').remove(b'/');
+
+/// Path where the releases are uploaded in versioned deploys (relative to the
+/// prefix).
+const RELEASES_PATH: &str = "releases/";
+
+/// Format of the releases ids (sorting them lexicographically sorts them
+/// chronologically as well).
+const RELEASE_ID_FORMAT: &str = "%Y%m%d%H%M%S";
 
 /// Deploy contribcard website to AWS S3.
 #[instrument(skip_all, err)]
@@ -38,29 +70,311 @@ pub(crate) async fn deploy(args: &S3Args) -> Result<()> {
     info!("deploying contribcard website..");
     let start = Instant::now();
 
-    // Check required environment variables
-    check_env_vars()?;
+    // Load objects rules and setup AWS S3 client
+    if args.versioned && args.keep_releases == 0 {
+        bail!("at least one release must be kept in versioned deploys");
+    }
+    let rules = ObjectRules::new(args.rules_file.as_deref())?;
+    let s3_client = setup_s3_client(&args.connection).await?;
+    let prefix = normalize_prefix(args.connection.prefix.as_deref());
+
+    // Deploy website
+    if args.versioned {
+        deploy_release(&s3_client, args, &prefix, &rules).await?;
+    } else {
+        deploy_in_place(&s3_client, args, &prefix, &rules).await?;
+    }
+
+    let duration = start.elapsed().as_secs_f64();
+    info!("contribcard website deployed! (took: {:.3}s)", duration);
+
+    Ok(())
+}
+
+/// Roll back a versioned deploy, making the release provided (or the one
+/// deployed before the current one) the current release again.
+#[instrument(skip_all, err)]
+pub(crate) async fn rollback(args: &RollbackArgs) -> Result<()> {
+    info!("rolling back contribcard website..");
 
     // Setup AWS S3 client
-    let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
-    let s3_client = aws_sdk_s3::Client::new(&config);
+    let s3_client = setup_s3_client(&args.connection).await?;
+    let bucket = &args.connection.bucket;
+    let prefix = normalize_prefix(args.connection.prefix.as_deref());
+
+    // Select the release to roll back to
+    let releases = get_releases(&s3_client, bucket, &prefix).await?;
+    let current_release = get_current_release(&s3_client, bucket, &prefix).await?;
+    let release = if let Some(release) = &args.release {
+        if !releases.contains(release) {
+            bail!("release {release} not found");
+        }
+        release.clone()
+    } else {
+        let Some(current_release) = &current_release else {
+            bail!("no current release found");
+        };
+        let previous_release = releases.iter().rev().find(|release| *release < current_release);
+        let Some(previous_release) = previous_release else {
+            bail!("no release found before the current one ({current_release})");
+        };
+        previous_release.clone()
+    };
+    if current_release.as_ref() == Some(&release) {
+        info!(release, "release is already the current one");
+        return Ok(());
+    }
 
-    // Get deployed objects
-    let deployed_objects = get_deployed_objects(&s3_client, &args.bucket).await?;
+    // Make it the current release
+    let delete_options = DeleteOptions {
+        enabled: args.delete,
+        max_delete_percent: args.max_delete_percent,
+        protected_prefixes: &args.protected_prefixes,
+    };
+    activate_release(&s3_client, bucket, &prefix, &release, &delete_options).await?;
+
+    info!(release, "contribcard website rolled back!");
+    Ok(())
+}
+
+/// Deploy the website updating the deployed objects in place.
+async fn deploy_in_place(
+    s3_client: &aws_sdk_s3::Client,
+    args: &S3Args,
+    prefix: &str,
+    rules: &ObjectRules,
+) -> Result<()> {
+    let bucket = &args.connection.bucket;
+
+    // Get deployed objects and prepare deploy plan
+    let deployed_objects = get_deployed_objects(s3_client, bucket, prefix).await?;
+    let plan = Plan::new(
+        &args.content_dir,
+        &deployed_objects,
+        &args.protected_prefixes,
+        true,
+    )?;
+    info!(
+        new = plan.new.len(),
+        changed = plan.changed.len(),
+        unchanged = plan.unchanged.len(),
+        stale = plan.stale.len(),
+        "deploy plan ready"
+    );
+
+    // Print plan and stop here when running in dry-run mode
+    if args.dry_run {
+        plan.print(args.plan_format)?;
+        return Ok(());
+    }
+
+    // Check stale objects can be deleted before making any changes
+    if args.delete {
+        plan.check_delete_threshold(deployed_objects.len(), args.max_delete_percent)?;
+    }
 
     // Upload website files (except index document)
-    upload_objects(&s3_client, &args.bucket, &args.content_dir, &deployed_objects).await?;
+    upload_objects(s3_client, bucket, prefix, rules, plan.objects_to_upload()).await?;
 
     // Upload index document if all the other files were uploaded successfully
-    let index_remote_checksum = deployed_objects.get(INDEX_DOCUMENT).and_then(|checksum| checksum.as_ref());
-    upload_index_document(&s3_client, &args.bucket, &args.content_dir, index_remote_checksum).await?;
+    if let Some(index_document) = plan.index_document_to_upload() {
+        upload_object(s3_client, bucket, prefix, rules, index_document)
+            .await
+            .context("error uploading index document")?;
+    }
 
-    let duration = start.elapsed().as_secs_f64();
-    info!("contribcard website deployed! (took: {:.3}s)", duration);
+    // Delete stale objects (if requested) once everything has been uploaded
+    if args.delete {
+        delete_objects(s3_client, bucket, prefix, &plan.stale).await?;
+    }
+
+    Ok(())
+}
+
+/// Deploy the website as a new release, making it the current one once all
+/// its objects are in place and deleting the releases that don't need to be
+/// kept anymore.
+///
+/// Objects that haven't changed since the current release are copied from it
+/// instead of being uploaded again.
+async fn deploy_release(
+    s3_client: &aws_sdk_s3::Client,
+    args: &S3Args,
+    prefix: &str,
+    rules: &ObjectRules,
+) -> Result<()> {
+    let bucket = &args.connection.bucket;
+
+    // Prepare new release id
+    let release = new_release_id(&args.content_dir)?;
+    let mut releases = get_releases(s3_client, bucket, prefix).await?;
+    if releases.contains(&release) {
+        bail!(
+            "release {release} has already been deployed (use the rollback subcommand to make it current again)"
+        );
+    }
+
+    // Get current release objects and prepare deploy plan
+    let current_release = get_current_release(s3_client, bucket, prefix).await?;
+    let current_release_prefix = current_release.as_ref().map(|current| release_prefix(prefix, current));
+    let deployed_objects = match &current_release_prefix {
+        Some(current_release_prefix) => {
+            get_deployed_objects(s3_client, bucket, current_release_prefix).await?
+        }
+        None => HashMap::new(),
+    };
+    let plan = Plan::new(&args.content_dir, &deployed_objects, &[], true)?;
+    info!(
+        release,
+        current_release,
+        new = plan.new.len(),
+        changed = plan.changed.len(),
+        unchanged = plan.unchanged.len(),
+        "deploy plan ready"
+    );
+
+    // Print plan and stop here when running in dry-run mode
+    if args.dry_run {
+        plan.print(args.plan_format)?;
+        return Ok(());
+    }
+
+    // Upload new and changed objects and copy the unchanged ones
+    let new_release_prefix = release_prefix(prefix, &release);
+    let objects_to_upload = plan.new.iter().chain(plan.changed.iter());
+    upload_objects(s3_client, bucket, &new_release_prefix, rules, objects_to_upload).await?;
+    if let Some(current_release_prefix) = &current_release_prefix {
+        copy_objects(
+            s3_client,
+            bucket,
+            current_release_prefix,
+            &new_release_prefix,
+            &plan.unchanged,
+        )
+        .await?;
+    }
+
+    // Switch the current release once all objects are in place
+    let delete_options = DeleteOptions {
+        enabled: args.delete,
+        max_delete_percent: args.max_delete_percent,
+        protected_prefixes: &args.protected_prefixes,
+    };
+    activate_release(s3_client, bucket, prefix, &release, &delete_options).await?;
+    info!(release, "current release switched");
+
+    // Delete releases that don't need to be kept anymore
+    releases.push(release.clone());
+    releases.sort();
+    let releases_to_delete = releases.len().saturating_sub(args.keep_releases);
+    for old_release in &releases[..releases_to_delete] {
+        if *old_release == release {
+            continue;
+        }
+        let old_release_prefix = release_prefix(prefix, old_release);
+        let keys: Vec<Key> =
+            list_objects(s3_client, bucket, &old_release_prefix).await?.into_keys().collect();
+        delete_objects(s3_client, bucket, &old_release_prefix, &keys).await?;
+        debug!(release = old_release, "old release deleted");
+    }
 
     Ok(())
 }
 
+/// Options used to delete the objects that are not needed anymore.
+struct DeleteOptions<'a> {
+    /// Whether the stale objects should be deleted or not.
+    enabled: bool,
+    /// Maximum percentage of the deployed objects that can be deleted.
+    max_delete_percent: f64,
+    /// Objects whose key starts with any of these prefixes are never deleted.
+    protected_prefixes: &'a [String],
+}
+
+/// Make the release provided the one served.
+///
+/// The data files are served from the release itself: the web application
+/// loads them from the release the current release pointer refers to, so
+/// updating the pointer (a single write) switches all of them at once. The
+/// rest of the release objects (pages, assets, images..) are copied to the
+/// prefix (server side) before that, leaving the index document for the end,
+/// as they are requested directly by the clients. When requested, the objects
+/// served that don't belong to the release are deleted once the pointer has
+/// been updated (except the releases, the pointer and the protected ones).
+#[instrument(skip(s3_client, bucket, prefix, delete_options), err)]
+async fn activate_release(
+    s3_client: &aws_sdk_s3::Client,
+    bucket: &str,
+    prefix: &str,
+    release: &str,
+    delete_options: &DeleteOptions<'_>,
+) -> Result<()> {
+    // Get the release objects served from the prefix and the ones currently
+    // served
+    let data_prefix = format!("{DATA_PATH}/");
+    let release_prefix = release_prefix(prefix, release);
+    let release_objects: HashMap<Key, Option<Checksum>> =
+        get_deployed_objects(s3_client, bucket, &release_prefix)
+            .await?
+            .into_iter()
+            .filter(|(key, _)| !key.starts_with(&data_prefix))
+            .collect();
+    let served_objects: HashMap<Key, Option<Checksum>> = get_deployed_objects(s3_client, bucket, prefix)
+        .await?
+        .into_iter()
+        .filter(|(key, _)| !key.starts_with(RELEASES_PATH) && key != CURRENT_RELEASE_KEY)
+        .collect();
+
+    // Check the objects that don't belong to the release can be deleted
+    // before making any changes
+    let mut stale_keys: Vec<Key> = served_objects
+        .keys()
+        .filter(|key| !release_objects.contains_key(*key))
+        .filter(|key| !delete_options.protected_prefixes.iter().any(|prefix| key.starts_with(prefix)))
+        .cloned()
+        .collect();
+    stale_keys.sort();
+    if delete_options.enabled {
+        check_delete_threshold(
+            stale_keys.len(),
+            served_objects.len(),
+            delete_options.max_delete_percent,
+        )?;
+    }
+
+    // Copy the release objects that differ from the ones served
+    let (index_document, mut keys_to_copy): (Vec<Key>, Vec<Key>) = release_objects
+        .iter()
+        .filter(|(key, checksum)| {
+            served_objects
+                .get(*key)
+                .is_none_or(|served_checksum| checksum.is_none() || served_checksum != *checksum)
+        })
+        .map(|(key, _)| key.clone())
+        .partition(|key| key == INDEX_DOCUMENT);
+    keys_to_copy.sort();
+    copy_objects(s3_client, bucket, &release_prefix, prefix, &keys_to_copy).await?;
+    copy_objects(s3_client, bucket, &release_prefix, prefix, &index_document)
+        .await
+        .context("error copying index document")?;
+
+    // Point the current release to it
+    set_current_release(s3_client, bucket, prefix, release).await?;
+
+    // Delete the objects served that don't belong to the release (if
+    // requested)
+    if delete_options.enabled {
+        delete_objects(s3_client, bucket, prefix, &stale_keys).await?;
+    }
+
+    debug!(
+        copied = keys_to_copy.len() + index_document.len(),
+        stale = stale_keys.len(),
+        "release activated"
+    );
+    Ok(())
+}
+
 /// Check that the required environment variables have been provided.
 #[instrument(skip_all, err)]
 fn check_env_vars() -> Result<()> {
@@ -76,27 +390,102 @@ fn check_env_vars() -> Result<()> {
     Ok(())
 }
 
-/// Get deployed objects returning their key and checksum.
+/// Setup the S3 client, using the custom endpoint, path style addressing and
+/// credentials profile provided (if any).
+async fn setup_s3_client(args: &S3ConnectionArgs) -> Result<aws_sdk_s3::Client> {
+    // Check required environment variables (not needed when using a profile)
+    if args.profile.is_none() && env::var("AWS_PROFILE").is_err() {
+        check_env_vars()?;
+    }
+
+    let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest());
+    if let Some(profile) = &args.profile {
+        loader = loader.profile_name(profile);
+    }
+    if let Some(endpoint_url) = &args.endpoint_url {
+        loader = loader.endpoint_url(endpoint_url);
+    }
+    let config = loader.load().await;
+    let s3_config =
+        aws_sdk_s3::config::Builder::from(&config).force_path_style(args.force_path_style).build();
+
+    Ok(aws_sdk_s3::Client::from_conf(s3_config))
+}
+
+/// Normalize the keys prefix provided, so that it's either empty or ends
+/// with a slash.
+fn normalize_prefix(prefix: Option<&str>) -> String {
+    match prefix.map(|prefix| prefix.trim_matches('/')) {
+        Some(prefix) if !prefix.is_empty() => format!("{prefix}/"),
+        _ => String::new(),
+    }
+}
+
+/// Get deployed objects returning their key (relative to the prefix
+/// provided) and checksum.
+///
+/// The checksum of objects uploaded using multipart uploads is read from
+/// their metadata, as their ETag is not an MD5 digest of their content.
 #[instrument(skip_all, err)]
 async fn get_deployed_objects(
     s3_client: &aws_sdk_s3::Client,
     bucket: &str,
+    prefix: &str,
 ) -> Result<HashMap<Key, Option<Checksum>>> {
-    let mut deployed_objects = HashMap::new();
+    let mut deployed_objects = list_objects(s3_client, bucket, prefix).await?;
+
+    // Get checksum from metadata for objects with multipart ETags
+    let multipart_keys: Vec<Key> = deployed_objects
+        .iter()
+        .filter(|(_, checksum)| checksum.as_ref().is_some_and(|checksum| checksum.contains('-')))
+        .map(|(key, _)| key.clone())
+        .collect();
+    let results: Vec<Result<(Key, Option<Checksum>)>> = stream::iter(multipart_keys)
+        .map(|key| async {
+            let output = s3_client
+                .head_object()
+                .bucket(bucket)
+                .key(format!("{prefix}{key}"))
+                .send()
+                .await
+                .context(format_err!("error getting object {key} metadata"))?;
+            let checksum = output.metadata.and_then(|mut metadata| metadata.remove(MD5_METADATA_KEY));
+            Ok((key, checksum))
+        })
+        .buffer_unordered(HEAD_OBJECTS_CONCURRENCY)
+        .collect()
+        .await;
+    for result in results {
+        let (key, checksum) = result?;
+        deployed_objects.insert(key, checksum);
+    }
+
+    Ok(deployed_objects)
+}
+
+/// List the objects with the prefix provided, returning their key (relative to
+/// the prefix) and ETag.
+async fn list_objects(
+    s3_client: &aws_sdk_s3::Client,
+    bucket: &str,
+    prefix: &str,
+) -> Result<HashMap<Key, Option<String>>> {
+    let mut objects = HashMap::new();
 
     let mut continuation_token = None;
     loop {
-        let mut request = s3_client.list_objects_v2().bucket(bucket);
+        let mut request = s3_client.list_objects_v2().bucket(bucket).prefix(prefix);
         if let Some(token) = continuation_token {
             request = request.continuation_token(token);
         }
         let output = request.send().await?;
-        if let Some(objects) = output.contents {
-            for object in objects {
-                let Some(key) = object.key else { continue };
-                let checksum = object.e_tag.map(|etag| etag.trim_matches('"').to_string());
-                deployed_objects.insert(key, checksum);
-            }
+        for object in output.contents.unwrap_or_default() {
+            let Some(key) = object.key else { continue };
+            let Some(key) = key.strip_prefix(prefix).map(ToString::to_string) else {
+                continue;
+            };
+            let etag = object.e_tag.map(|etag| etag.trim_matches('"').to_string());
+            objects.insert(key, etag);
         }
         if !output.is_truncated.unwrap_or(false) {
             break;
@@ -104,75 +493,235 @@ async fn get_deployed_objects(
         continuation_token = output.next_continuation_token;
     }
 
-    Ok(deployed_objects)
+    Ok(objects)
 }
 
-/// Upload contribcard website files to S3 bucket.
+/// Upload the objects provided to the S3 bucket.
 #[instrument(skip_all, err)]
-async fn upload_objects(
+async fn upload_objects<'a>(
     s3_client: &aws_sdk_s3::Client,
     bucket: &str,
-    content_dir: &PathBuf,
-    deployed_objects: &HashMap<Key, Option<Checksum>>,
+    prefix: &str,
+    rules: &ObjectRules,
+    objects: impl Iterator<Item = &'a LocalObject>,
 ) -> Result<()> {
-    // Upload files in the content directory to the bucket provided
-    let results: Vec<Result<()>> = stream::iter(WalkDir::new(content_dir))
-        .map(|entry| async {
-            // Check if the entry is a regular file
-            let entry = entry?;
-            if !entry.file_type().is_file() {
-                return Ok(());
-            }
-
-            // Prepare object's key
-            let file = entry.path();
-            let key = file
-                .display()
-                .to_string()
-                .trim_start_matches(content_dir.display().to_string().as_str())
-                .trim_start_matches('/')
-                .to_string();
-
-            // We'll upload the index document at the end when all the other
-            // files have been uploaded successfully
-            if key == INDEX_DOCUMENT {
-                return Ok(());
-            }
+    let results: Vec<Result<()>> = stream::iter(objects)
+        .map(|object| upload_object(s3_client, bucket, prefix, rules, object))
+        .buffer_unordered(UPLOAD_FILES_CONCURRENCY)
+        .collect()
+        .await;
 
-            // Skip files that start with a dot
-            if key.starts_with('.') {
-                return Ok(());
-            }
+    // Process results
+    let mut errors_found = false;
+    let mut errors = String::new();
+    for result in results {
+        if let Err(err) = result {
+            errors_found = true;
+            writeln!(&mut errors, "- {err:?}")?;
+        }
+    }
+    if errors_found {
+        bail!("{errors}");
+    }
 
-            // Skip objects that haven't changed
-            let checksum = md5sum(file)?;
-            if let Some(Some(remote_checksum)) = deployed_objects.get(&key)
-                && checksum == *remote_checksum
-            {
-                return Ok(());
-            }
+    Ok(())
+}
 
-            // Prepare object's body and content type
-            let body = ByteStream::from_path(file).await?;
-            let content_type = mime_guess::from_path(&key)
-                .first()
-                .ok_or(format_err!("cannot detect content type of key: {})", &key))?;
+/// Upload the object provided to the S3 bucket, applying the cache control
+/// and metadata of the first rule matching its key. Large files are uploaded
+/// using multipart uploads.
+async fn upload_object(
+    s3_client: &aws_sdk_s3::Client,
+    bucket: &str,
+    prefix: &str,
+    rules: &ObjectRules,
+    object: &LocalObject,
+) -> Result<()> {
+    // Prepare object's key, cache control and metadata
+    let key = format!("{prefix}{}", object.key);
+    let rule = rules.get(&object.key);
+    let cache_control = rule.and_then(|rule| rule.cache_control.clone());
+    let mut metadata = rule.map(|rule| rule.metadata.clone()).unwrap_or_default();
+    metadata.insert(MD5_METADATA_KEY.to_string(), object.checksum.clone());
 
-            // Upload file
+    // Upload file
+    if object.size > MULTIPART_UPLOAD_THRESHOLD {
+        let upload_id = s3_client
+            .create_multipart_upload()
+            .bucket(bucket)
+            .key(&key)
+            .content_type(&object.content_type)
+            .set_content_encoding(object.content_encoding.clone())
+            .set_cache_control(cache_control)
+            .set_metadata(Some(metadata))
+            .send()
+            .await
+            .context(format_err!(
+                "error creating multipart upload for file {}",
+                object.key
+            ))?
+            .upload_id
+            .ok_or(format_err!("missing multipart upload id"))?;
+
+        if let Err(err) = upload_parts(s3_client, bucket, &key, &upload_id, object).await {
             s3_client
-                .put_object()
+                .abort_multipart_upload()
                 .bucket(bucket)
                 .key(&key)
-                .body(body)
-                .content_type(content_type.essence_str())
+                .upload_id(&upload_id)
                 .send()
                 .await
-                .context(format_err!("error uploading file {key}"))?;
+                .context(format_err!(
+                    "error aborting multipart upload for file {}",
+                    object.key
+                ))?;
+            return Err(err);
+        }
+    } else {
+        let body = ByteStream::from_path(&object.path).await?;
+        s3_client
+            .put_object()
+            .bucket(bucket)
+            .key(&key)
+            .body(body)
+            .content_type(&object.content_type)
+            .set_content_encoding(object.content_encoding.clone())
+            .set_cache_control(cache_control)
+            .set_metadata(Some(metadata))
+            .send()
+            .await
+            .context(format_err!("error uploading file {}", object.key))?;
+    }
 
-            debug!(?key, "file uploaded");
+    debug!(key = object.key, "file uploaded");
+    Ok(())
+}
+
+/// Upload the parts of the object provided and complete the multipart upload.
+/// Parts are read from the file as they are uploaded, so that the whole file
+/// isn't loaded in memory.
+async fn upload_parts(
+    s3_client: &aws_sdk_s3::Client,
+    bucket: &str,
+    key: &str,
+    upload_id: &str,
+    object: &LocalObject,
+) -> Result<()> {
+    let mut completed_parts = vec![];
+    let mut offset = 0;
+    let mut part_number = 1;
+    while offset < object.size {
+        let length = MULTIPART_UPLOAD_PART_SIZE.min(object.size - offset);
+        let body = ByteStream::read_from()
+            .path(&object.path)
+            .offset(offset)
+            .length(Length::Exact(length))
+            .build()
+            .await?;
+        let output = s3_client
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(body)
+            .send()
+            .await
+            .context(format_err!(
+                "error uploading part {part_number} of file {}",
+                object.key
+            ))?;
+        completed_parts
+            .push(CompletedPart::builder().set_e_tag(output.e_tag).part_number(part_number).build());
+        offset += length;
+        part_number += 1;
+    }
+
+    s3_client
+        .complete_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .upload_id(upload_id)
+        .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(completed_parts)).build())
+        .send()
+        .await
+        .context(format_err!(
+            "error completing multipart upload for file {}",
+            object.key
+        ))?;
+
+    Ok(())
+}
+
+/// Delete the objects provided from the S3 bucket (in batches).
+#[instrument(skip_all, err)]
+async fn delete_objects(
+    s3_client: &aws_sdk_s3::Client,
+    bucket: &str,
+    prefix: &str,
+    keys: &[Key],
+) -> Result<()> {
+    for batch in keys.chunks(DELETE_OBJECTS_BATCH_SIZE) {
+        // Prepare batch of objects to delete
+        let objects = batch
+            .iter()
+            .map(|key| ObjectIdentifier::builder().key(format!("{prefix}{key}")).build())
+            .collect::<Result<Vec<_>, _>>()?;
+        let delete = Delete::builder().set_objects(Some(objects)).quiet(true).build()?;
+
+        // Delete objects
+        let output = s3_client
+            .delete_objects()
+            .bucket(bucket)
+            .delete(delete)
+            .send()
+            .await
+            .context("error deleting objects")?;
+        let errors = output.errors.unwrap_or_default();
+        if !errors.is_empty() {
+            let mut msg = String::new();
+            for error in errors {
+                writeln!(
+                    &mut msg,
+                    "- {}: {}",
+                    error.key.unwrap_or_default(),
+                    error.message.unwrap_or_default()
+                )?;
+            }
+            bail!("error deleting some objects:\n{msg}");
+        }
+
+        debug!(count = batch.len(), "stale objects deleted");
+    }
+
+    Ok(())
+}
+
+/// Copy the objects provided (keys relative to the source prefix) to the
+/// destination prefix. The objects' metadata is copied as well.
+#[instrument(skip_all, err)]
+async fn copy_objects(
+    s3_client: &aws_sdk_s3::Client,
+    bucket: &str,
+    source_prefix: &str,
+    destination_prefix: &str,
+    keys: &[Key],
+) -> Result<()> {
+    let results: Vec<Result<()>> = stream::iter(keys)
+        .map(|key| async move {
+            let copy_source = format!("{bucket}/{source_prefix}{key}");
+            s3_client
+                .copy_object()
+                .bucket(bucket)
+                .copy_source(utf8_percent_encode(&copy_source, COPY_SOURCE_ENCODE_SET).to_string())
+                .key(format!("{destination_prefix}{key}"))
+                .send()
+                .await
+                .context(format_err!("error copying object {key}"))?;
+            debug!(key, "object copied");
             Ok(())
         })
-        .buffer_unordered(UPLOAD_FILES_CONCURRENCY)
+        .buffer_unordered(COPY_OBJECTS_CONCURRENCY)
         .collect()
         .await;
 
@@ -192,48 +741,100 @@ async fn upload_objects(
     Ok(())
 }
 
-/// Upload contribcard website index document to S3 bucket.
+/// Prepare the id of the release for the content directory provided from the
+/// date of its build (or from the current date if it has no build manifest).
+fn new_release_id(content_dir: &Path) -> Result<String> {
+    let date = match Manifest::read(content_dir)? {
+        Some(manifest) => DateTime::parse_from_rfc3339(&manifest.created_at)
+            .context("invalid build manifest creation date")?
+            .with_timezone(&Utc),
+        None => Utc::now(),
+    };
+
+    Ok(date.format(RELEASE_ID_FORMAT).to_string())
+}
+
+/// Return the prefix of the keys of the release provided.
+fn release_prefix(prefix: &str, release: &str) -> String {
+    format!("{prefix}{RELEASES_PATH}{release}/")
+}
+
+/// Get the ids of the releases deployed (sorted from oldest to newest).
 #[instrument(skip_all, err)]
-async fn upload_index_document(
+async fn get_releases(s3_client: &aws_sdk_s3::Client, bucket: &str, prefix: &str) -> Result<Vec<String>> {
+    let releases_prefix = format!("{prefix}{RELEASES_PATH}");
+    let mut releases = vec![];
+
+    let mut continuation_token = None;
+    loop {
+        let mut request = s3_client.list_objects_v2().bucket(bucket).prefix(&releases_prefix).delimiter("/");
+        if let Some(token) = continuation_token {
+            request = request.continuation_token(token);
+        }
+        let output = request.send().await?;
+        for common_prefix in output.common_prefixes.unwrap_or_default() {
+            let Some(release) = common_prefix
+                .prefix
+                .as_deref()
+                .and_then(|common_prefix| common_prefix.strip_prefix(&releases_prefix))
+                .map(|release| release.trim_end_matches('/').to_string())
+            else {
+                continue;
+            };
+            releases.push(release);
+        }
+        if !output.is_truncated.unwrap_or(false) {
+            break;
+        }
+        continuation_token = output.next_continuation_token;
+    }
+    releases.sort();
+
+    Ok(releases)
+}
+
+/// Get the id of the current release, if any.
+#[instrument(skip_all, err)]
+async fn get_current_release(
     s3_client: &aws_sdk_s3::Client,
     bucket: &str,
-    content_dir: &Path,
-    remote_checksum: Option<&Checksum>,
-) -> Result<()> {
-    // Prepare object's checksum, key, body and content type
-    let file = content_dir.join(INDEX_DOCUMENT);
-    let checksum = md5sum(&file)?;
-    let key = INDEX_DOCUMENT.to_string();
-    let body = ByteStream::from_path(&file).await?;
-    let content_type = mime::TEXT_HTML.essence_str();
-
-    // Check if the remote copy is up to date
-    if let Some(remote_checksum) = remote_checksum
-        && checksum == *remote_checksum
-    {
-        return Ok(());
-    }
+    prefix: &str,
+) -> Result<Option<String>> {
+    let result = s3_client
+        .get_object()
+        .bucket(bucket)
+        .key(format!("{prefix}{CURRENT_RELEASE_KEY}"))
+        .send()
+        .await;
+    let output = match result {
+        Ok(output) => output,
+        Err(err) if err.as_service_error().is_some_and(GetObjectError::is_no_such_key) => return Ok(None),
+        Err(err) => return Err(err).context("error getting current release"),
+    };
+    let content = output.body.collect().await?.into_bytes();
+    let release = String::from_utf8(content.to_vec())?.trim().to_string();
+
+    Ok(Some(release))
+}
 
-    // Upload file
+/// Point the current release to the release provided.
+#[instrument(skip(s3_client, bucket, prefix), err)]
+async fn set_current_release(
+    s3_client: &aws_sdk_s3::Client,
+    bucket: &str,
+    prefix: &str,
+    release: &str,
+) -> Result<()> {
     s3_client
         .put_object()
         .bucket(bucket)
-        .key(key)
-        .body(body)
-        .content_type(content_type)
+        .key(format!("{prefix}{CURRENT_RELEASE_KEY}"))
+        .body(ByteStream::from(release.as_bytes().to_vec()))
+        .content_type("text/plain")
+        .cache_control("no-cache")
         .send()
         .await
-        .context("error uploading index document")?;
+        .context("error setting current release")?;
 
-    debug!("index document uploaded");
     Ok(())
 }
-
-/// Calculate the MD5 digest of a file.
-fn md5sum(path: &Path) -> Result<String> {
-    let mut hasher = Md5::new();
-    hasher.update(fs::read(path)?);
-    let result = hasher.finalize();
-
-    Ok(format!("{result:x}"))
-}
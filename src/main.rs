@@ -5,8 +5,8 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use build::build;
-use clap::{Args, Parser, Subcommand};
-use deploy::s3;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use deploy::{git, local, s3};
 use serve::serve;
 
 mod build;
@@ -87,20 +87,204 @@ struct DeployArgs {
 /// Provider used to deploy the contribcard website.
 #[derive(Subcommand)]
 enum Provider {
+    /// Deploy contribcard website to a branch of a git repository (i.e. for
+    /// GitHub Pages).
+    Git(GitArgs),
+
+    /// Deploy contribcard website to a local directory.
+    Local(LocalArgs),
+
     /// Deploy contribcard website to AWS S3.
     S3(S3Args),
+
+    /// Roll back a versioned AWS S3 deploy to a previous release.
+    Rollback(RollbackArgs),
+}
+
+/// Git provider arguments.
+#[derive(Args)]
+struct GitArgs {
+    /// Location of the contribcard website files (build subcommand output).
+    #[arg(long)]
+    content_dir: PathBuf,
+
+    /// Repository to deploy the contribcard website files to (remote url or
+    /// path of a local repository).
+    #[arg(long)]
+    repository: String,
+
+    /// Branch the contribcard website files will be committed to (it will be
+    /// created if it doesn't exist).
+    #[arg(long, default_value = "gh-pages")]
+    branch: String,
+
+    /// ContribCard settings file. When provided, a CNAME file with the
+    /// domain of the website base url will be added.
+    #[arg(long)]
+    settings_file: Option<PathBuf>,
+
+    /// Name of the author of the deploy commits.
+    #[arg(long, default_value = "contribcard")]
+    author_name: String,
+
+    /// Email of the author of the deploy commits.
+    #[arg(long, default_value = "contribcard@users.noreply.github.com")]
+    author_email: String,
+
+    /// Print the deploy plan without making any changes.
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+
+    /// Format used to print the deploy plan in dry-run mode.
+    #[arg(long, value_enum, default_value_t = PlanFormat::Table)]
+    plan_format: PlanFormat,
+
+    /// Files whose path starts with this prefix will never be deleted (can
+    /// be provided multiple times).
+    #[arg(long = "protected-prefix")]
+    protected_prefixes: Vec<String>,
+}
+
+/// Local directory provider arguments.
+#[derive(Args)]
+struct LocalArgs {
+    /// Location of the contribcard website files (build subcommand output).
+    #[arg(long)]
+    content_dir: PathBuf,
+
+    /// Directory to deploy the contribcard website files to (i.e. the one
+    /// served by nginx). It will be replaced atomically on each deploy (on
+    /// Unix systems it's a symbolic link to the current version).
+    #[arg(long)]
+    target_dir: PathBuf,
+
+    /// Print the deploy plan without making any changes.
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+
+    /// Format used to print the deploy plan in dry-run mode.
+    #[arg(long, value_enum, default_value_t = PlanFormat::Table)]
+    plan_format: PlanFormat,
+
+    /// Files whose path starts with this prefix will never be deleted (can
+    /// be provided multiple times).
+    #[arg(long = "protected-prefix")]
+    protected_prefixes: Vec<String>,
 }
 
 /// AWS S3 provider arguments.
 #[derive(Args)]
 struct S3Args {
+    #[command(flatten)]
+    connection: S3ConnectionArgs,
+
+    /// Location of the contribcard website files (build subcommand output).
+    #[arg(long)]
+    content_dir: PathBuf,
+
+    /// Upload each build to its own release (releases/{build_id}/) and make
+    /// it the current one once all files have been uploaded. The data files
+    /// are served from the current release (switched by updating a pointer
+    /// object), and the rest of its objects are copied to the prefix, where
+    /// the website is served from (when deleting, the ones that don't belong
+    /// to it are removed unless they are protected).
+    #[arg(long, default_value_t = false)]
+    versioned: bool,
+
+    /// Number of releases to keep in versioned deploys (including the current
+    /// one). Older releases are deleted once the new one is live.
+    #[arg(long, default_value_t = 5)]
+    keep_releases: usize,
+
+    /// YAML file with the rules used to set the cache control and metadata
+    /// of the objects uploaded (some default rules are used if not provided).
+    /// Rules are only applied to objects uploaded in this deploy.
+    #[arg(long)]
+    rules_file: Option<PathBuf>,
+
+    /// Print the deploy plan without making any changes.
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+
+    /// Format used to print the deploy plan in dry-run mode.
+    #[arg(long, value_enum, default_value_t = PlanFormat::Table)]
+    plan_format: PlanFormat,
+
+    /// Delete objects in the bucket that are not present in the content
+    /// directory (once all files have been uploaded successfully).
+    #[arg(long, default_value_t = false)]
+    delete: bool,
+
+    /// Maximum percentage of the deployed objects that can be deleted. The
+    /// deploy will be aborted if more objects would be deleted.
+    #[arg(long, default_value_t = 25.0)]
+    max_delete_percent: f64,
+
+    /// Objects whose key starts with this prefix will never be deleted (can
+    /// be provided multiple times).
+    #[arg(long = "protected-prefix")]
+    protected_prefixes: Vec<String>,
+}
+
+/// AWS S3 rollback arguments.
+#[derive(Args)]
+struct RollbackArgs {
+    #[command(flatten)]
+    connection: S3ConnectionArgs,
+
+    /// Release to roll back to. The release deployed before the current one
+    /// will be used when none is provided.
+    #[arg(long)]
+    release: Option<String>,
+
+    /// Delete objects served that don't belong to the release rolled back to
+    /// (once it has been made the current one).
+    #[arg(long, default_value_t = false)]
+    delete: bool,
+
+    /// Maximum percentage of the deployed objects that can be deleted. The
+    /// rollback will be aborted if more objects would be deleted.
+    #[arg(long, default_value_t = 25.0)]
+    max_delete_percent: f64,
+
+    /// Objects whose key starts with this prefix will never be deleted (can
+    /// be provided multiple times).
+    #[arg(long = "protected-prefix")]
+    protected_prefixes: Vec<String>,
+}
+
+/// AWS S3 connection arguments.
+#[derive(Args)]
+struct S3ConnectionArgs {
     /// Bucket to copy the contribcard website files to.
     #[arg(long)]
     bucket: String,
 
-    /// Location of the contribcard website files (build subcommand output).
+    /// Custom endpoint URL of the S3 compatible service (i.e. MinIO or
+    /// Cloudflare R2).
     #[arg(long)]
-    content_dir: PathBuf,
+    endpoint_url: Option<String>,
+
+    /// Use path style addressing (bucket in the path instead of in the host).
+    #[arg(long, default_value_t = false)]
+    force_path_style: bool,
+
+    /// Prefix added to the keys of all the objects (the website will be
+    /// deployed to this "directory" of the bucket).
+    #[arg(long)]
+    prefix: Option<String>,
+
+    /// Name of the profile to load the credentials and region from (instead
+    /// of the AWS environment variables).
+    #[arg(long)]
+    profile: Option<String>,
+}
+
+/// Format used to print the deploy plan.
+#[derive(Clone, Copy, ValueEnum)]
+enum PlanFormat {
+    Json,
+    Table,
 }
 
 /// Serve command arguments.
@@ -133,7 +317,10 @@ async fn main() -> Result<()> {
     match &cli.command {
         Command::Build(args) => build(args).await?,
         Command::Deploy(args) => match &args.provider {
+            Provider::Git(args) => git::deploy(args)?,
+            Provider::Local(args) => local::deploy(args)?,
             Provider::S3(args) => s3::deploy(args).await?,
+            Provider::Rollback(args) => s3::rollback(args).await?,
         },
         Command::Serve(args) => serve(args).await?,
     }
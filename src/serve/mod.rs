@@ -3,12 +3,21 @@
 use std::{env, net::SocketAddr};
 
 use anyhow::Result;
-use axum::{Router, routing::get_service};
+use axum::{
+    Router,
+    extract::{Request, State},
+    http::Uri,
+    middleware,
+    routing::get_service,
+};
 use tokio::{net::TcpListener, signal};
 use tower_http::services::{ServeDir, ServeFile};
 use tracing::{info, instrument};
 
-use crate::ServeArgs;
+use crate::{
+    ServeArgs,
+    build::{DATA_PATH, layout::DataLayout},
+};
 
 /// Serve contribcard website.
 #[instrument(skip_all)]
@@ -25,9 +34,15 @@ pub(crate) async fn serve(args: &ServeArgs) -> Result<()> {
         dir
     };
     let index_path = content.join("index.html");
-    let router: Router<()> = Router::new().fallback(get_service(
-        ServeDir::new(&content).not_found_service(ServeFile::new(&index_path)),
-    ));
+    let layout = DataLayout::read(&content.join(DATA_PATH))?;
+    let router: Router<()> = Router::new()
+        .fallback(get_service(
+            ServeDir::new(&content)
+                .precompressed_br()
+                .precompressed_gzip()
+                .not_found_service(ServeFile::new(&index_path).precompressed_br().precompressed_gzip()),
+        ))
+        .layer(middleware::map_request_with_state(layout, rewrite_data_file_uri));
 
     // Setup and launch HTTP server
     let addr: SocketAddr = args.addr.parse()?;
@@ -43,6 +58,29 @@ pub(crate) async fn serve(args: &ServeArgs) -> Result<()> {
     Ok(())
 }
 
+/// Rewrite requests of contributors data files using the flat layout (i.e.
+/// /data/{login}.json) to the location defined by the layout descriptor.
+async fn rewrite_data_file_uri(State(layout): State<Option<DataLayout>>, mut request: Request) -> Request {
+    let Some(layout) = layout else {
+        return request;
+    };
+    let Some(file_name) = request.uri().path().strip_prefix(&format!("/{DATA_PATH}/")) else {
+        return request;
+    };
+    let Some(login) = file_name.strip_suffix(".json") else {
+        return request;
+    };
+    if login.is_empty() || login.starts_with('_') || login.contains('/') {
+        return request;
+    }
+
+    let path = layout.contributor_path(login);
+    if let Ok(uri) = format!("/{DATA_PATH}/{}", path.display()).parse::<Uri>() {
+        *request.uri_mut() = uri;
+    }
+    request
+}
+
 /// Return a future that will complete when the program is asked to stop via a
 /// ctrl+c or terminate signal.
 async fn shutdown_signal() {
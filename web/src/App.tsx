@@ -2,13 +2,17 @@ import { Route, Router } from '@solidjs/router';
 
 import Layout from './layout';
 import ContributorCard from './layout/contributor';
-import Search from './layout/search';
+import Home from './layout/home';
+import Leaderboards from './layout/leaderboards';
+import WrappedCard from './layout/wrapped';
 
 function App() {
   return (
     <Router root={Layout}>
+      <Route path="/_leaderboards" component={Leaderboards} />
+      <Route path="/wrapped/:year/:id" component={WrappedCard} />
       <Route path="/:id" component={ContributorCard} />
-      <Route path="*" component={Search} />
+      <Route path="*" component={Home} />
     </Router>
   );
 }
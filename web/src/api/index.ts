@@ -1,7 +1,25 @@
-import { Contributor, UserInfo } from '../types';
+import {
+  Contributor,
+  DataLayout,
+  Leaderboard,
+  LeaderboardsIndex,
+  SearchIndexMeta,
+  Stats,
+  UserInfo,
+  Wrapped,
+} from '../types';
+
+const DEFAULT_LAYOUT: DataLayout = { version: 1, shard_depth: 0 };
+const PADDING_CHAR = '_';
+// Pointer to the current release in versioned deploys (data files are served
+// from the release it points to)
+const CURRENT_RELEASE_PATH = '/current';
+const RELEASE_ID_PATTERN = /^\d{14}$/;
 
 class API_CLASS {
   private BASE_PATH = '/data';
+  private dataPath: Promise<string> | null = null;
+  private layout: Promise<DataLayout> | null = null;
 
   private async handleErrors(res: Response) {
     if (!res.ok) {
@@ -35,16 +53,75 @@ class API_CLASS {
       .catch((error) => Promise.reject(error));
   }
 
-  public async getAllContributors(): Promise<UserInfo> {
-    return this.apiFetch(
-      `${import.meta.env.MODE === 'development' ? `/static${this.BASE_PATH}` : this.BASE_PATH}/_all_contributors.json`
-    );
+  public async getSearchIndexMeta(): Promise<SearchIndexMeta> {
+    return this.apiFetch(`${await this.getDataPath()}/_index/_meta.json`);
+  }
+
+  public async getSearchIndexShard(prefix: string): Promise<UserInfo> {
+    return this.apiFetch(`${await this.getDataPath()}/_index/${encodeURIComponent(prefix)}.json`);
+  }
+
+  public async getSearchIndexNgramsShard(ngram: string): Promise<UserInfo> {
+    return this.apiFetch(`${await this.getDataPath()}/_index/_ngrams/${encodeURIComponent(ngram)}.json`);
+  }
+
+  public async getStats(): Promise<Stats> {
+    return this.apiFetch(`${await this.getDataPath()}/_stats.json`);
+  }
+
+  public async getLeaderboardsIndex(): Promise<LeaderboardsIndex> {
+    return this.apiFetch(`${await this.getDataPath()}/_leaderboards/_index.json`);
+  }
+
+  public async getLeaderboard(path: string): Promise<Leaderboard> {
+    return this.apiFetch(`${await this.getDataPath()}/_leaderboards/${path}.json`);
+  }
+
+  // Data files path is resolved only once. In versioned deploys they are
+  // served from the current release, so all of them come from the same one
+  private getDataPath(): Promise<string> {
+    if (this.dataPath === null) {
+      if (import.meta.env.MODE === 'development') {
+        this.dataPath = Promise.resolve(`/static${this.BASE_PATH}`);
+      } else {
+        this.dataPath = fetch(CURRENT_RELEASE_PATH, { cache: 'no-cache' })
+          .then(async (res) => {
+            const release = res.ok ? (await res.text()).trim() : '';
+            return RELEASE_ID_PATTERN.test(release) ? `/releases/${release}${this.BASE_PATH}` : this.BASE_PATH;
+          })
+          .catch(() => this.BASE_PATH);
+      }
+    }
+    return this.dataPath;
+  }
+
+  // Data files layout is fetched only once (flat layout is used as fallback)
+  private getDataLayout(): Promise<DataLayout> {
+    if (this.layout === null) {
+      this.layout = this.getDataPath()
+        .then((dataPath) => this.apiFetch(`${dataPath}/_layout.json`))
+        .then((layout: DataLayout | null) => layout || DEFAULT_LAYOUT)
+        .catch(() => DEFAULT_LAYOUT);
+    }
+    return this.layout;
+  }
+
+  private getContributorPath(id: string, layout: DataLayout): string {
+    const chars = Array.from(id.toLowerCase());
+    const shards = [];
+    for (let i = 0; i < layout.shard_depth; i++) {
+      shards.push(chars[i] || PADDING_CHAR);
+    }
+    return [...shards, `${id}.json`].join('/');
+  }
+
+  public async getContributorInfo(id: string): Promise<Contributor> {
+    const layout = await this.getDataLayout();
+    return this.apiFetch(`${await this.getDataPath()}/${this.getContributorPath(id, layout)}`);
   }
 
-  public getContributorInfo(id: string): Promise<Contributor> {
-    return this.apiFetch(
-      `${import.meta.env.MODE === 'development' ? `/static${this.BASE_PATH}` : this.BASE_PATH}/${id}.json`
-    );
+  public async getWrapped(year: string, id: string): Promise<Wrapped> {
+    return this.apiFetch(`${await this.getDataPath()}/wrapped/${year}/${id}.json`);
   }
 }
 
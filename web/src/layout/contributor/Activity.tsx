@@ -0,0 +1,70 @@
+import { createMemo, For } from 'solid-js';
+
+import styles from './Activity.module.css';
+
+interface Month {
+  key: string;
+  label: string;
+  total: number;
+}
+
+interface Props {
+  months: { [key: string]: number } | null;
+  longestStreak: number;
+  currentStreak: number;
+  mostActiveDay: string;
+  lastContributionTs: number;
+}
+
+const NUM_MONTHS = 12;
+const MONTHS = ['Jan', 'Feb', 'Mar', 'Apr', 'May', 'Jun', 'Jul', 'Aug', 'Sep', 'Oct', 'Nov', 'Dec'];
+
+const Activity = (props: Props) => {
+  // Last months of activity, ending on the month of the last contribution
+  // (months keys are in UTC, as the ones in the contributor data)
+  const months = createMemo(() => {
+    const last = new Date(props.lastContributionTs * 1000);
+    const items: Month[] = [];
+    for (let i = NUM_MONTHS - 1; i >= 0; i--) {
+      const date = new Date(Date.UTC(last.getUTCFullYear(), last.getUTCMonth() - i, 1));
+      const key = `${date.getUTCFullYear()}-${String(date.getUTCMonth() + 1).padStart(2, '0')}`;
+      items.push({
+        key: key,
+        label: MONTHS[date.getUTCMonth()],
+        total: (props.months || {})[key] || 0,
+      });
+    }
+    return items;
+  });
+  const max = createMemo(() => Math.max(1, ...months().map((m) => m.total)));
+
+  return (
+    <div>
+      <div class={`d-flex flex-row align-items-end mt-2 ${styles.chart}`}>
+        <For each={months()}>
+          {(month) => (
+            <div class="d-flex flex-column align-items-center flex-grow-1" title={`${month.key}: ${month.total}`}>
+              <div class={styles.bar} style={{ height: `${(month.total / max()) * 100}%` }} />
+              <div class={`text-muted ${styles.label}`}>{month.label}</div>
+            </div>
+          )}
+        </For>
+      </div>
+      <div class={`d-flex flex-row flex-wrap text-muted mt-2 ${styles.stats}`}>
+        <div class="me-3">
+          Longest streak: <span class="fw-bold">{props.longestStreak}</span>{' '}
+          {props.longestStreak === 1 ? 'week' : 'weeks'}
+        </div>
+        <div class="me-3">
+          Current streak: <span class="fw-bold">{props.currentStreak}</span>{' '}
+          {props.currentStreak === 1 ? 'week' : 'weeks'}
+        </div>
+        <div>
+          Most active on <span class="fw-bold">{props.mostActiveDay}</span>
+        </div>
+      </div>
+    </div>
+  );
+};
+
+export default Activity;
@@ -1,14 +1,16 @@
 import { A, useLocation, useParams } from '@solidjs/router';
-import { createSignal, JSXElement, Match, onCleanup, onMount, Show, Switch } from 'solid-js';
+import { createSignal, For, JSXElement, Match, onCleanup, onMount, Show, Switch } from 'solid-js';
 
 import API from '../../api';
 import clotributor from '../../assets/clotributor.png';
 import { ContributionKind, Contributor } from '../../types';
+import currentStreak from '../../utils/currentStreak';
 import prettifyNumber from '../../utils/prettifyNumber';
 import updateMetaTags from '../../utils/updateMetaTags';
 import ExternalLink from '../common/ExternalLink';
 import Image from '../common/Image';
 import Loading from '../common/Loading';
+import Activity from './Activity';
 import Badges from './Badges';
 import styles from './Contributor.module.css';
 import ShareContributorLink from './ShareContributorLink';
@@ -102,7 +104,7 @@ const ContributorCard = () => {
         setContributor(null);
       }
     }
-    updateMetaTags(`${window.location.origin}${location.pathname}`);
+    updateMetaTags(`${window.location.origin}${location.pathname}`, params.id);
   });
 
   onCleanup(() => {
@@ -220,6 +222,14 @@ const ContributorCard = () => {
             <span class="fw-bold">{contributor()!.repositories.length}</span>{' '}
             {contributor()!.repositories.length === 1 ? 'repository' : 'repositories'}
           </div>
+          <Show when={contributor()!.ranking.total}>
+            {(ranking) => (
+              <div class={`mt-2 lh-1 text-muted text-truncate ${styles.ranking}`}>
+                Top <span class="fw-bold">{ranking().top_percent}%</span> of{' '}
+                {prettifyNumber(ranking().contributors, 1)} contributors
+              </div>
+            )}
+          </Show>
         </div>
 
         <div class="mt-4">
@@ -243,6 +253,72 @@ const ContributorCard = () => {
             </div>
           </ExternalLink>
 
+          <Show when={contributor()!.first_merged_pull_request}>
+            {(pr) => (
+              <div class="mt-4">
+                <div class={`text-muted text-uppercase ${styles.generalTitle}`}>First merged pull request</div>
+                <ExternalLink
+                  class={`mt-2 ${styles.card}`}
+                  href={`https://github.com/${pr().owner}/${pr().repository}/pull/${pr().number}`}
+                  underlined={false}
+                >
+                  <div class="d-flex flex-row align-items-top">
+                    <div class={`pe-2 text-muted ${styles.contribIcon}`}>
+                      <ContributionKindIcon kind={ContributionKind.PR} />
+                    </div>
+                    <div class={`d-flex flex-column justify-content-between ${styles.firstContribContent}`}>
+                      <div class={`fw-bold text-truncate w-100 ${styles.firstContributionRepo}`}>
+                        {pr().owner}/{pr().repository}
+                      </div>
+                      <div class={`fw-semibold text-truncate w-100 ${styles.firstContributionLink}`}>
+                        {pr().title.trim()}
+                      </div>
+                      <div class={`text-muted ${styles.date}`}>
+                        <small>{formatDate(pr().ts)}</small>
+                      </div>
+                    </div>
+                  </div>
+                </ExternalLink>
+              </div>
+            )}
+          </Show>
+
+          <Show when={(contributor()!.milestones || []).length > 0}>
+            <div class="mt-4">
+              <div class={`text-muted text-uppercase ${styles.generalTitle}`}>
+                Milestones ({contributor()!.milestones!.length})
+              </div>
+              <div class="d-flex flex-row flex-wrap mt-2">
+                <For each={contributor()!.milestones}>
+                  {(milestone) => (
+                    <div
+                      class={`me-2 mb-2 ${styles.badge}`}
+                      title={`${milestone.contribution.owner}/${milestone.contribution.repository}: ${milestone.contribution.title}`}
+                    >
+                      <div class={styles.badgeContent}>
+                        {milestone.title} <small class="text-muted">({formatDate(milestone.ts)})</small>
+                      </div>
+                    </div>
+                  )}
+                </For>
+              </div>
+            </div>
+          </Show>
+
+          <div class="mt-4">
+            <div class={`text-muted text-uppercase ${styles.generalTitle}`}>Activity</div>
+            <Activity
+              months={contributor()!.activity.months}
+              longestStreak={contributor()!.activity.weekly_streak.longest}
+              currentStreak={currentStreak(
+                contributor()!.activity.weekly_streak.last,
+                contributor()!.activity.weekly_streak.last_week
+              )}
+              mostActiveDay={contributor()!.activity.most_active_day_of_week}
+              lastContributionTs={contributor()!.last_contribution.ts}
+            />
+          </div>
+
           <div class="mt-4">
             <div class={`text-muted text-uppercase ${styles.generalTitle}`}>
               Years contributing ({contributor()!.years.length})
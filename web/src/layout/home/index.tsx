@@ -0,0 +1,13 @@
+import Overview from '../overview';
+import Search from '../search';
+
+const Home = () => {
+  return (
+    <>
+      <Search />
+      <Overview />
+    </>
+  );
+};
+
+export default Home;
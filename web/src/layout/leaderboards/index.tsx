@@ -0,0 +1,83 @@
+import { A } from '@solidjs/router';
+import { createSignal, For, onMount, Show } from 'solid-js';
+
+import API from '../../api';
+import { Leaderboard, LeaderboardsIndex } from '../../types';
+import prettifyNumber from '../../utils/prettifyNumber';
+import updateMetaTags from '../../utils/updateMetaTags';
+import Image from '../common/Image';
+import Loading from '../common/Loading';
+import styles from './Leaderboards.module.css';
+
+const ALL_TIME = 'all';
+
+const Leaderboards = () => {
+  const [index, setIndex] = createSignal<LeaderboardsIndex | null>();
+  const [selected, setSelected] = createSignal<string>(ALL_TIME);
+  const [leaderboard, setLeaderboard] = createSignal<Leaderboard | null>();
+
+  async function fetchLeaderboard(path: string) {
+    setSelected(path);
+    setLeaderboard();
+    try {
+      setLeaderboard(await API.getLeaderboard(path));
+    } catch {
+      setLeaderboard(null);
+    }
+  }
+
+  onMount(async () => {
+    updateMetaTags();
+    try {
+      setIndex(await API.getLeaderboardsIndex());
+    } catch {
+      setIndex(null);
+    }
+    fetchLeaderboard(ALL_TIME);
+  });
+
+  return (
+    <div class="mt-4">
+      <div class={`mb-3 ${styles.title}`}>Top contributors</div>
+
+      <Show when={index()}>
+        <select
+          class={`form-select rounded-0 mb-3 ${styles.select}`}
+          value={selected()}
+          onChange={(e) => fetchLeaderboard(e.currentTarget.value)}
+        >
+          <option value={ALL_TIME}>All time</option>
+          <For each={index()!.years}>{(year) => <option value={year}>{year}</option>}</For>
+          <For each={index()!.projects}>
+            {(project) => <option value={`projects/${project}`}>{project}</option>}
+          </For>
+          <For each={index()!.repositories}>
+            {(repo) => <option value={`repositories/${repo}`}>{repo}</option>}
+          </For>
+        </select>
+      </Show>
+
+      <Show when={leaderboard() !== undefined} fallback={<Loading />}>
+        <Show
+          when={leaderboard() !== null}
+          fallback={<div class="text-muted text-center py-4">No leaderboard available.</div>}
+        >
+          <For each={leaderboard()!.contributors}>
+            {(contributor) => (
+              <A class={`d-flex flex-row align-items-center py-2 ${styles.item}`} href={`/${contributor.login}/`}>
+                <div class={`fw-bold text-muted me-3 ${styles.rank}`}>{contributor.rank}</div>
+                <div class={`me-3 ${styles.miniAvatar}`}>
+                  <Image contributorId={contributor.id} login={contributor.login} class="d-block w-100 h-100 mask" />
+                </div>
+                <div class="fw-semibold text-truncate flex-grow-1">{contributor.login}</div>
+                <div class="text-muted ms-2">{prettifyNumber(contributor.contributions, 1)}</div>
+              </A>
+            )}
+          </For>
+        </Show>
+      </Show>
+    </div>
+  );
+};
+
+export default Leaderboards;
@@ -0,0 +1,87 @@
+import { A } from '@solidjs/router';
+import { createSignal, For, onMount, Show } from 'solid-js';
+
+import API from '../../api';
+import { Stats } from '../../types';
+import activeContributors from '../../utils/activeContributors';
+import prettifyNumber from '../../utils/prettifyNumber';
+import styles from './Overview.module.css';
+
+const Overview = () => {
+  const [stats, setStats] = createSignal<Stats | null>(null);
+
+  onMount(async () => {
+    try {
+      setStats(await API.getStats());
+    } catch {
+      setStats(null);
+    }
+  });
+
+  const years = () => Object.entries(stats()!.contributors_per_year || {});
+  const maxPerYear = () => Math.max(1, ...years().map(([, total]) => total));
+  const activeLastDays = (days: number) => activeContributors(stats()!.last_contributions_per_day, days);
+
+  return (
+    <Show when={stats()}>
+      <div class={`mt-5 pt-3 ${styles.overview}`}>
+        <div class={`text-muted text-uppercase ${styles.title}`}>Community overview</div>
+
+        <div class="d-flex flex-row flex-wrap justify-content-between mt-3">
+          <div class={styles.stat}>
+            <div class={styles.statValue}>{prettifyNumber(stats()!.contributions.total, 1)}</div>
+            <div class="text-muted">contributions</div>
+          </div>
+          <div class={styles.stat}>
+            <div class={styles.statValue}>{prettifyNumber(activeLastDays(30), 1)}</div>
+            <div class="text-muted">active last 30 days</div>
+          </div>
+          <div class={styles.stat}>
+            <div class={styles.statValue}>{prettifyNumber(activeLastDays(365), 1)}</div>
+            <div class="text-muted">active last year</div>
+          </div>
+        </div>
+
+        <div class="mt-4">
+          <div class={`text-muted text-uppercase ${styles.title}`}>Contributors per year</div>
+          <div class={`d-flex flex-row align-items-end mt-2 ${styles.chart}`}>
+            <For each={years()}>
+              {([year, total]) => (
+                <div class="d-flex flex-column align-items-center flex-grow-1" title={`${year}: ${total}`}>
+                  <div class={styles.bar} style={{ height: `${(total / maxPerYear()) * 100}%` }} />
+                  <div class={`text-muted ${styles.label}`}>{year.slice(2)}</div>
+                </div>
+              )}
+            </For>
+          </div>
+        </div>
+
+        <Show when={(stats()!.top_repositories || []).length > 0}>
+          <div class="mt-4">
+            <div class={`text-muted text-uppercase ${styles.title}`}>Top repositories</div>
+            <For each={stats()!.top_repositories}>
+              {(repo) => (
+                <div class={`d-flex flex-row justify-content-between mt-2 ${styles.repo}`}>
+                  <div class="fw-semibold text-truncate">{repo.name}</div>
+                  <div class="text-muted text-nowrap ms-2">
+                    {prettifyNumber(repo.contributors, 1)} contributors
+                  </div>
+                </div>
+              )}
+            </For>
+          </div>
+        </Show>
+
+        <Show when={window.leaderboards}>
+          <div class="mt-3 text-center">
+            <A class={styles.link} href="/_leaderboards">
+              See top contributors
+            </A>
+          </div>
+        </Show>
+      </div>
+    </Show>
+  );
+};
+
+export default Overview;
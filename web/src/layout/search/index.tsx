@@ -2,9 +2,10 @@ import { useNavigate } from '@solidjs/router';
 import { createEffect, createSignal, For, on, onCleanup, onMount, Show } from 'solid-js';
 
 import {
+  useContributorsDataCount,
   useContributorsDataInfo,
-  useContributorsDataList,
   useContributorsDataLoader,
+  useContributorsDataSearch,
 } from '../../stores/contributorsData';
 import prettifyNumber from '../../utils/prettifyNumber';
 import updateMetaTags from '../../utils/updateMetaTags';
@@ -13,14 +14,17 @@ import Image from '../common/Image';
 import styles from './Search.module.css';
 
 const SEARCH_DELAY = 2 * 100; // 200ms
-const MIN_CHARACTERS_SEARCH = 3;
+// Logins shorter than the index prefix length can be found as well (the
+// search index shards them by the full login)
+const MIN_CHARACTERS_SEARCH = 1;
 const MAX_CONTRIBUTORS = 10;
 
 const Search = () => {
   const navigate = useNavigate();
-  const currentContributors = useContributorsDataList();
+  const contributorsCount = useContributorsDataCount();
   const contributorsInfo = useContributorsDataInfo();
   const loadContributors = useContributorsDataLoader();
+  const searchContributors = useContributorsDataSearch();
   const [inputEl, setInputEl] = createSignal<HTMLInputElement>();
   const [dropdownRef, setDropdownRef] = createSignal<HTMLInputElement>();
   const [value, setValue] = createSignal<string>('');
@@ -66,15 +70,10 @@ const Search = () => {
     }
   };
 
-  const onSearch = (text: string) => {
-    const lowerText = text.toLowerCase();
-    let counter = 0;
-    const filteredContributors = currentContributors()!.filter((c: string) => {
-      if (counter < MAX_CONTRIBUTORS && c.toLowerCase().startsWith(lowerText)) {
-        counter++;
-        return c;
-      }
-    });
+  const onSearch = async (text: string) => {
+    const filteredContributors = await searchContributors(text, MAX_CONTRIBUTORS);
+    // Discard results if the search text has changed in the meantime
+    if (text !== value()) return;
     if (filteredContributors.length > 0) {
       const isInputFocused = inputEl() === document.activeElement;
       // We have to be sure that input has focus to display results
@@ -108,7 +107,7 @@ const Search = () => {
     cleanItemsSearch();
     setValue('');
     forceBlur();
-    navigate(`/${contributorId}`, {
+    navigate(`/${contributorId}/`, {
       replace: false,
       scroll: true, // default
     });
@@ -146,7 +145,7 @@ const Search = () => {
   createEffect(
     on(value, () => {
       // Do not search if there are no contributors
-      if (currentContributors().length === 0) return;
+      if (!contributorsCount()) return;
 
       const isInputFocused = inputEl() === document.activeElement;
       if (isInputFocused) {
@@ -156,7 +155,7 @@ const Search = () => {
           setDropdownTimeout(
             setTimeout(() => {
               setHighlightedContributor(null);
-              onSearch(value());
+              void onSearch(value());
             }, SEARCH_DELAY)
           );
         } else {
@@ -166,14 +165,6 @@ const Search = () => {
     })
   );
 
-  createEffect(
-    on(currentContributors, () => {
-      if (value() === '') {
-        setVisibleContributors(currentContributors());
-      }
-    })
-  );
-
   onCleanup(() => {
     if (dropdownTimeout() !== null) {
       clearTimeout(dropdownTimeout()!);
@@ -182,7 +173,6 @@ const Search = () => {
 
   onMount(() => {
     updateMetaTags();
-    setVisibleContributors(currentContributors());
     void loadContributors();
   });
 
@@ -236,14 +226,14 @@ const Search = () => {
         <div class={`d-flex align-items-center justify-content-center ${styles.countingMessage}`}>
           <div class={`fw-bold position-relative me-2 ${styles.countingNumber}`}>
             <Show
-              when={currentContributors().length > 0}
+              when={contributorsCount()}
               fallback={
                 <div class={`pe-2 ${styles.dotsLoading}`} role="status">
                   <span class="visually-hidden">Loading...</span>
                 </div>
               }
             >
-              {prettifyNumber(currentContributors().length, 1)}
+              {prettifyNumber(contributorsCount()!, 1)}
             </Show>
           </div>
           <div class={`text-truncate ${styles.countingText}`}>contributors and counting!</div>
@@ -0,0 +1,121 @@
+import { A, useLocation, useParams } from '@solidjs/router';
+import { createSignal, For, onCleanup, onMount, Show } from 'solid-js';
+
+import API from '../../api';
+import { Wrapped } from '../../types';
+import prettifyNumber from '../../utils/prettifyNumber';
+import updateMetaTags from '../../utils/updateMetaTags';
+import Image from '../common/Image';
+import Loading from '../common/Loading';
+import ShareContributorLink from '../contributor/ShareContributorLink';
+import styles from './Wrapped.module.css';
+
+const MONTHS = [
+  'January',
+  'February',
+  'March',
+  'April',
+  'May',
+  'June',
+  'July',
+  'August',
+  'September',
+  'October',
+  'November',
+  'December',
+];
+
+const WrappedCard = () => {
+  const location = useLocation();
+  const params = useParams();
+  const [wrapped, setWrapped] = createSignal<Wrapped | null | undefined>();
+
+  const busiestMonth = () => MONTHS[parseInt(wrapped()!.busiest_month.month.split('-')[1]) - 1];
+
+  async function fetchWrapped(year: string, id: string) {
+    try {
+      setWrapped(await API.getWrapped(year, id));
+    } catch {
+      setWrapped(null);
+    }
+  }
+
+  onMount(() => {
+    if (params.year && params.id) {
+      fetchWrapped(params.year, params.id);
+    } else {
+      setWrapped(null);
+    }
+    updateMetaTags(`${window.location.origin}${location.pathname}`, params.id);
+  });
+
+  onCleanup(() => {
+    setWrapped();
+  });
+
+  return (
+    <Show when={wrapped() !== undefined} fallback={<Loading />}>
+      <Show
+        when={wrapped() !== null}
+        fallback={<div class={`pt-3 pt-md-5 pb-3 ${styles.title}`}>No year in review available.</div>}
+      >
+        <div class="pt-0 pt-md-3">
+          <div class="d-flex flex-row align-items-center mb-4">
+            <div class="me-3 avatar">
+              <Image class="d-block w-100 h-100 mask" login={wrapped()!.login} contributorId={wrapped()!.id} />
+            </div>
+            <div class={`fw-semibold text-truncate ${styles.title}`}>
+              {wrapped()!.login}'s {wrapped()!.year} in review
+            </div>
+          </div>
+
+          <div class={`text-muted ${styles.highlight}`}>
+            <span class="fw-bold">{prettifyNumber(wrapped()!.contributions.total, 1)}</span>{' '}
+            {wrapped()!.contributions.total === 1 ? 'contribution' : 'contributions'}, most of them in{' '}
+            <span class="fw-bold">{busiestMonth()}</span>
+          </div>
+
+          <Show when={(wrapped()!.new_repositories || []).length > 0}>
+            <div class={`mt-2 text-muted ${styles.highlight}`}>
+              <span class="fw-bold">{wrapped()!.new_repositories!.length}</span> new{' '}
+              {wrapped()!.new_repositories!.length === 1 ? 'repository' : 'repositories'} contributed to
+            </div>
+          </Show>
+
+          <div class="mt-4">
+            <div class={`text-muted text-uppercase ${styles.generalTitle}`}>Top repositories</div>
+            <For each={wrapped()!.top_repositories}>
+              {(repo) => (
+                <div class={`d-flex flex-row justify-content-between mt-2 ${styles.repo}`}>
+                  <div class="fw-semibold text-truncate">{repo.name}</div>
+                  <div class="text-muted ms-2">{prettifyNumber(repo.contributions, 1)}</div>
+                </div>
+              )}
+            </For>
+          </div>
+
+          <div class="mt-4">
+            <div class={`text-muted text-uppercase ${styles.generalTitle}`}>First and last contributions</div>
+            <div class={`mt-2 text-truncate ${styles.repo}`}>
+              {wrapped()!.first_contribution.owner}/{wrapped()!.first_contribution.repository}:{' '}
+              {wrapped()!.first_contribution.title.trim()}
+            </div>
+            <div class={`mt-2 text-truncate ${styles.repo}`}>
+              {wrapped()!.last_contribution.owner}/{wrapped()!.last_contribution.repository}:{' '}
+              {wrapped()!.last_contribution.title.trim()}
+            </div>
+          </div>
+
+          <div class="d-flex flex-row align-items-center justify-content-center mt-4">
+            <ShareContributorLink />
+            <A class="ms-3" href={`/${wrapped()!.login}/`}>
+              See full card
+            </A>
+          </div>
+        </div>
+      </Show>
+    </Show>
+  );
+};
+
+export default WrappedCard;
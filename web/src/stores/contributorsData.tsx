@@ -1,31 +1,100 @@
 import { createContext, createSignal, ParentComponent, useContext } from 'solid-js';
 
 import API from '../api';
-import { UserInfo } from '../types';
+import { SearchIndexMeta, UserInfo } from '../types';
+
+const NGRAM_LENGTH = 3;
+
+// Get the distinct trigrams of the text provided
+const getNgrams = (text: string): string[] => {
+  const ngrams = new Set<string>();
+  for (let i = 0; i + NGRAM_LENGTH <= text.length; i++) {
+    ngrams.add(text.slice(i, i + NGRAM_LENGTH));
+  }
+  return Array.from(ngrams);
+};
 
 function useContributorsDataProvider() {
-  const [contributors, setContributors] = createSignal<string[]>([]);
-  const [contributorsInfo, setContributorsInfo] = createSignal<UserInfo | null>();
+  const [indexMeta, setIndexMeta] = createSignal<SearchIndexMeta | null>(null);
+  const [contributorsInfo, setContributorsInfo] = createSignal<UserInfo>({});
+  // Shards already fetched (missing shards are stored as empty ones)
+  const shards = new Map<string, Promise<UserInfo>>();
   let loading = false;
 
   const loadContributors = async () => {
-    if (contributorsInfo() || loading) {
+    if (indexMeta() || loading) {
       return;
     }
     loading = true;
     try {
-      const allContributors = await API.getAllContributors();
-      setContributors(Object.keys(allContributors).sort(Intl.Collator().compare));
-      setContributorsInfo(allContributors);
+      setIndexMeta(await API.getSearchIndexMeta());
     } finally {
       loading = false;
     }
   };
 
+  const getShard = (key: string, fetchShard: () => Promise<UserInfo>): Promise<UserInfo> => {
+    let shard = shards.get(key);
+    if (!shard) {
+      shard = fetchShard()
+        .then((info: UserInfo | null): UserInfo => {
+          const shardInfo: UserInfo = info || {};
+          setContributorsInfo({ ...contributorsInfo(), ...shardInfo });
+          return shardInfo;
+        })
+        .catch((): UserInfo => ({}));
+      shards.set(key, shard);
+    }
+    return shard;
+  };
+
+  const searchContributors = async (text: string, limit: number): Promise<string[]> => {
+    const meta = indexMeta();
+    const lowerText = text.toLowerCase();
+    if (!meta || lowerText.length === 0) {
+      return [];
+    }
+
+    // Contributors whose login starts with the text provided (when the text
+    // is shorter than the prefix length, the shard keyed by the full text
+    // contains the contributors whose login matches it exactly)
+    const prefix = lowerText.slice(0, meta.prefix_length);
+    const prefixShard = await getShard(prefix, () => API.getSearchIndexShard(prefix));
+    const contributors = Object.keys(prefixShard)
+      .filter((c: string) => c.toLowerCase().startsWith(lowerText))
+      .sort(Intl.Collator().compare)
+      .slice(0, limit);
+
+    // Fill the remaining results with the contributors whose login shares
+    // most trigrams with the text provided (when n-grams are available)
+    if (meta.ngrams && contributors.length < limit) {
+      const ngrams = getNgrams(lowerText);
+      const ngramsShards = await Promise.all(
+        ngrams.map((ngram: string) => getShard(`_ngrams/${ngram}`, () => API.getSearchIndexNgramsShard(ngram)))
+      );
+      const scores = new Map<string, number>();
+      ngramsShards.forEach((shard: UserInfo) => {
+        Object.keys(shard).forEach((c: string) => {
+          scores.set(c, (scores.get(c) || 0) + 1);
+        });
+      });
+      const minScore = Math.ceil(ngrams.length / 2);
+      const fuzzyContributors = Array.from(scores.entries())
+        .filter(([c, score]) => score >= minScore && !contributors.includes(c))
+        .sort(([c1, s1], [c2, s2]) => s2 - s1 || Intl.Collator().compare(c1, c2))
+        .slice(0, limit - contributors.length)
+        .map(([c]) => c);
+      contributors.push(...fuzzyContributors);
+    }
+
+    return contributors;
+  };
+
   return {
-    contributors: contributors,
+    contributorsCount: () => indexMeta()?.contributors,
     contributorsInfo: contributorsInfo,
     loadContributors: loadContributors,
+    searchContributors: searchContributors,
   };
 }
 
@@ -46,8 +115,8 @@ export function useContributorsData() {
   return context;
 }
 
-export function useContributorsDataList() {
-  return useContributorsData().contributors;
+export function useContributorsDataCount() {
+  return useContributorsData().contributorsCount;
 }
 
 export function useContributorsDataInfo() {
@@ -57,3 +126,7 @@ export function useContributorsDataInfo() {
 export function useContributorsDataLoader() {
   return useContributorsData().loadContributors;
 }
+
+export function useContributorsDataSearch() {
+  return useContributorsData().searchContributors;
+}
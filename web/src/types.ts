@@ -2,6 +2,17 @@ export interface UserInfo {
   [key: string]: number;
 }
 
+export interface DataLayout {
+  version: number;
+  shard_depth: number;
+}
+
+export interface SearchIndexMeta {
+  contributors: number;
+  prefix_length: number;
+  ngrams: boolean;
+}
+
 export interface Contributor {
   id: number;
   login: string;
@@ -9,9 +20,23 @@ export interface Contributor {
     total: number;
     by_kind: {
       [key in ContributionKind]: number;
+    } & {
+      pull_request_merged: number;
+      issue_completed: number;
     };
   };
   years: number[];
+  activity: {
+    months: { [key: string]: number } | null;
+    weekly_streak: {
+      longest: number;
+      // Length of the most recent streak and number of its last week (weeks
+      // since 1970-01-05), used to compute the current streak
+      last: number;
+      last_week: number | null;
+    };
+    most_active_day_of_week: string;
+  };
   repositories: string[];
   first_contribution: {
     number?: number;
@@ -22,6 +47,105 @@ export interface Contributor {
     title: string;
     ts: number;
   };
+  milestones: Milestone[] | null;
+  ranking: {
+    total: Ranking | null;
+    by_kind: { [key: string]: Ranking } | null;
+    repositories: (Ranking & { name: string })[] | null;
+    projects: (Ranking & { name: string })[] | null;
+  };
+  last_contribution: {
+    number?: number;
+    sha?: string;
+    kind: ContributionKind;
+    owner: string;
+    repository: string;
+    title: string;
+    ts: number;
+  };
+  first_merged_pull_request?: {
+    number: number;
+    kind: ContributionKind;
+    owner: string;
+    repository: string;
+    title: string;
+    ts: number;
+  };
+}
+
+export interface Stats {
+  contributors: number;
+  contributions: {
+    total: number;
+    by_kind: { [key: string]: number } | null;
+  };
+  contributors_per_year: { [key: string]: number } | null;
+  new_contributors_per_month: { [key: string]: number } | null;
+  top_repositories: { name: string; contributions: number; contributors: number }[] | null;
+  last_contributions_per_day: { [key: string]: number } | null;
+}
+
+export interface LeaderboardsIndex {
+  years: string[];
+  repositories: string[];
+  projects: string[];
+}
+
+export interface Leaderboard {
+  scope: string;
+  name: string;
+  contributors: {
+    rank: number;
+    id: number;
+    login: string;
+    contributions: number;
+  }[];
+}
+
+export interface Wrapped {
+  year: number;
+  id: number;
+  login: string;
+  contributions: {
+    total: number;
+    by_kind: { [key: string]: number } | null;
+  };
+  top_repositories: { name: string; contributions: number }[] | null;
+  busiest_month: { month: string; contributions: number };
+  new_repositories: string[] | null;
+  first_contribution: Contribution;
+  last_contribution: Contribution;
+}
+
+export interface Contribution {
+  number?: number;
+  sha?: string;
+  kind: ContributionKind;
+  owner: string;
+  repository: string;
+  title: string;
+  ts: number;
+}
+
+export interface Ranking {
+  rank: number;
+  top_percent: number;
+  contributors: number;
+}
+
+export interface Milestone {
+  id: string;
+  title: string;
+  ts: number;
+  contribution: {
+    number?: number;
+    sha?: string;
+    kind: ContributionKind | 'review';
+    owner: string;
+    repository: string;
+    title: string;
+    ts: number;
+  };
 }
 
 export enum ContributionKind {
@@ -0,0 +1,13 @@
+const DAY_MS = 24 * 60 * 60 * 1000;
+
+// Count the contributors whose last contribution happened within the last
+// days provided. It's computed here (and not at build time) so that it
+// doesn't go stale between builds.
+const activeContributors = (lastContributionsPerDay: { [key: string]: number } | null, days: number): number => {
+  const since = new Date(Date.now() - days * DAY_MS).toISOString().slice(0, 10);
+  return Object.entries(lastContributionsPerDay || {})
+    .filter(([day]) => day >= since)
+    .reduce((total, [, contributors]) => total + contributors, 0);
+};
+
+export default activeContributors;
@@ -0,0 +1,16 @@
+const DAY_MS = 24 * 60 * 60 * 1000;
+// Monday used as the origin of the week numbers computed at build time
+const FIRST_WEEK_MS = Date.UTC(1970, 0, 5);
+
+// Compute the current weekly streak from the most recent one. It's computed
+// here (and not at build time) so that it doesn't go stale between builds.
+const currentStreak = (last: number, lastWeek: number | null): number => {
+  if (lastWeek === null) {
+    return 0;
+  }
+
+  const currentWeek = Math.floor((Date.now() - FIRST_WEEK_MS) / DAY_MS / 7);
+  return lastWeek >= currentWeek - 1 ? last : 0;
+};
+
+export default currentStreak;
@@ -1,5 +1,10 @@
-const updateMetaTags = (url?: string) => {
+const updateMetaTags = (url?: string, login?: string) => {
   document.querySelector(`meta[property='og:url']`)!.setAttribute('content', url || window.location.href);
+  if (login && window.og_images) {
+    const imageUrl = `${window.location.origin}/images/og/${login}.png`;
+    document.querySelector(`meta[property='og:image']`)!.setAttribute('content', imageUrl);
+    document.querySelector(`meta[name='twitter:image']`)!.setAttribute('content', imageUrl);
+  }
 };
 
 export default updateMetaTags;
@@ -4,6 +4,8 @@ declare global {
   interface Window {
     contributors: UserInfo;
     email_subject: string;
+    leaderboards: boolean;
+    og_images: boolean;
     social_message: string;
   }
 }